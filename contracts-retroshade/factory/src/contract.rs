@@ -3,7 +3,9 @@ use soroban_sdk::{contract, contractimpl, Env};
 
 use crate::{
     events, pool,
-    storage::{get_pool_hash, has_pool_hash, set_pool_hash},
+    storage::{
+        get_policy_nft_hash, get_pool_hash, has_pool_hash, set_policy_nft_hash, set_pool_hash,
+    },
     types::Error,
 };
 
@@ -20,6 +22,7 @@ mod mercury_types {
         pub pool_admin: Address,
         pub pool_token: Address,
         pub pool_oracle: Address,
+        pub pool_policy_nft: Address,
         pub asset_symbol: Symbol,
         pub periods_in_min: i32,
         pub volatility: i128,
@@ -28,13 +31,13 @@ mod mercury_types {
 }
 // ---------------------------------------------------------------------------
 
-use soroban_sdk::{Address, BytesN, Symbol};
+use soroban_sdk::{Address, Bytes, BytesN};
 
 #[contract]
 pub struct Factory;
 
 pub trait Interface {
-    fn init_factory(env: Env, pool_hash: BytesN<32>) -> Result<(), Error>;
+    fn init_factory(env: Env, pool_hash: BytesN<32>, policy_nft_hash: BytesN<32>) -> Result<(), Error>;
 
     #[allow(clippy::too_many_arguments)]
     fn initialize(
@@ -43,9 +46,7 @@ pub trait Interface {
         salt: BytesN<32>,
         token: Address,
         oracle: Address,
-        symbol: Symbol,
-        external_asset: bool,
-        oracle_asset: Option<Address>,
+        assets: pool::AssetPair,
         periods_in_min: i32,
         volatility: i128,
         multiplier: i32,
@@ -54,12 +55,13 @@ pub trait Interface {
 
 #[contractimpl]
 impl Interface for Factory {
-    // Set the pool hash before initializing any pools
-    fn init_factory(env: Env, pool_hash: BytesN<32>) -> Result<(), Error> {
+    // Set the pool and policy NFT hashes before initializing any pools
+    fn init_factory(env: Env, pool_hash: BytesN<32>, policy_nft_hash: BytesN<32>) -> Result<(), Error> {
         if has_pool_hash(&env) {
             return Err(Error::AlreadyInitialized);
         }
         set_pool_hash(&env, pool_hash);
+        set_policy_nft_hash(&env, policy_nft_hash);
         Ok(())
     }
 
@@ -71,9 +73,7 @@ impl Interface for Factory {
         salt: BytesN<32>,
         token: Address,           // token used by the pool to collect premiums and pay benefits
         oracle: Address,          // Reflector oracle address
-        symbol: Symbol,
-        external_asset: bool,     // true if oracle sources data from CEX/DEX/etc.
-        oracle_asset: Option<Address>, // ignored when external_asset = true
+        assets: pool::AssetPair,  // followed asset and, for pair pools, the asset it is priced in
         periods_in_min: i32,     // length of each insurance period
         volatility: i128,         // stroops movement threshold for trigger
         multiplier: i32,          // see whitepaper’s premium/benefit section
@@ -81,21 +81,27 @@ impl Interface for Factory {
         let pool_hash = get_pool_hash(&env);
 
         let pool_address = env.deployer()
-            .with_current_contract(salt)
+            .with_current_contract(salt.clone())
             .deploy(pool_hash);
 
         let pool = pool::Client::new(&env, &pool_address);
 
+        // Policies are minted from a companion contract, deployed with a salt derived from the pool's
+        // and initialized by the pool with itself as minter
+        let policy_nft_salt = env.crypto().sha256(&Bytes::from(salt));
+        let policy_nft_address = env.deployer()
+            .with_current_contract(policy_nft_salt)
+            .deploy(get_policy_nft_hash(&env));
+
         pool.initialize(
             &admin,
             &token,
             &oracle,
-            &symbol,
-            &external_asset,
-            &oracle_asset,
+            &assets,
             &periods_in_min,
             &volatility,
             &multiplier,
+            &Some(policy_nft_address.clone()),
         );
 
         // Your existing contract event (traditional Soroban event)
@@ -112,7 +118,8 @@ impl Interface for Factory {
                 pool_admin: admin.clone(),
                 pool_token: token.clone(),
                 pool_oracle: oracle.clone(),
-                asset_symbol: symbol,
+                pool_policy_nft: policy_nft_address,
+                asset_symbol: assets.symbol,
                 periods_in_min,              
                 volatility,                   
                 multiplier,                   
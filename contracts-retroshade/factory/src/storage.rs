@@ -15,3 +15,13 @@ pub fn has_pool_hash(env: &Env) -> bool {
     let key = DataKey::PoolHash;
     env.storage().instance().has(&key)
 }
+
+pub fn set_policy_nft_hash(env: &Env, policy_nft_hash: BytesN<32>) {
+    let key = DataKey::PolicyNftHash;
+    env.storage().instance().set(&key, &policy_nft_hash);
+}
+
+pub fn get_policy_nft_hash(env: &Env) -> BytesN<32> {
+    let key = DataKey::PolicyNftHash;
+    env.storage().instance().get(&key).unwrap()
+}
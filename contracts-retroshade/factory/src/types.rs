@@ -3,6 +3,7 @@ use soroban_sdk::{contracterror, contracttype, Address};
 #[contracttype]
 pub enum DataKey {
     PoolHash,
+    PolicyNftHash,
 }
 
 #[derive(Copy, Clone, Debug)]
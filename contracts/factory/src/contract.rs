@@ -1,15 +1,21 @@
 use crate::{
     events, pool,
-    storage::{get_pool_hash, has_pool_hash, set_pool_hash},
+    storage::{
+        get_policy_nft_hash, get_pool_hash, has_pool_hash, set_policy_nft_hash, set_pool_hash,
+    },
     types::Error,
 };
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Symbol};
+use soroban_sdk::{contract, contractimpl, Address, Bytes, BytesN, Env};
 
 #[contract]
 pub struct Factory;
 
 pub trait Interface {
-    fn init_factory(env: Env, pool_hash: BytesN<32>) -> Result<(), Error>;
+    fn init_factory(
+        env: Env,
+        pool_hash: BytesN<32>,
+        policy_nft_hash: BytesN<32>,
+    ) -> Result<(), Error>;
 
     fn initialize(
         env: Env,
@@ -17,9 +23,7 @@ pub trait Interface {
         salt: BytesN<32>,
         token: Address,
         oracle: Address,
-        symbol: Symbol,
-        external_asset: bool,
-        oracle_asset: Option<Address>,
+        assets: pool::AssetPair,
         periods_in_days: i32,
         volatility: i128,
         multiplier: i32,
@@ -28,13 +32,18 @@ pub trait Interface {
 
 #[contractimpl]
 impl Interface for Factory {
-    // set the pool hash before initializing any pools
-    fn init_factory(env: Env, pool_hash: BytesN<32>) -> Result<(), Error> {
+    // set the pool and policy NFT hashes before initializing any pools
+    fn init_factory(
+        env: Env,
+        pool_hash: BytesN<32>,
+        policy_nft_hash: BytesN<32>,
+    ) -> Result<(), Error> {
         if has_pool_hash(&env) {
             return Err(Error::AlreadyInitialized);
         }
 
         set_pool_hash(&env, pool_hash);
+        set_policy_nft_hash(&env, policy_nft_hash);
 
         Ok(())
     }
@@ -46,27 +55,35 @@ impl Interface for Factory {
         salt: BytesN<32>,
         token: Address, // the token used by rthe pool to collect insurance premiums and pay amounts
         oracle: Address, // must be a Reflector oracle address
-        symbol: Symbol,
-        external_asset: bool, // true/false: internal if using an oracle that sources datat from Stellar pubnet (for Stellar Classic and Soroban assets), external if oracle's data source is external CEX/DEX (for any external tokens/assets/symbols)
-        oracle_asset: Option<Address>, // if the asset is external, set this to None or whatever value (it will not be taken into account)
-        periods_in_days: i32,          // days each "insurance period" will last
-        volatility: i128, // amount in stroops that the price of the followed asse needs to move with respect to the base asset (USD/USDC) in order for the triggering condition to happen
+        assets: pool::AssetPair, // the followed asset (symbol/Stellar address, internal or external oracle data source) and, for pair pools (ex: an LST against its underlying), the asset it is priced in
+        periods_in_days: i32,    // days each "insurance period" will last
+        volatility: i128, // amount in stroops that the price of the followed asse needs to move with respect to the base asset (USD/USDC, or the quote asset for pair pools) in order for the triggering condition to happen
         multiplier: i32, // see section "Insurance Premiums and how Benefits are Calculated" of the whitepaper
     ) -> Result<Address, Error> {
         let pool_hash = get_pool_hash(&env);
-        let pool_address = env.deployer().with_current_contract(salt).deploy(pool_hash);
+        let pool_address = env
+            .deployer()
+            .with_current_contract(salt.clone())
+            .deploy(pool_hash);
         let pool = pool::Client::new(&env, &pool_address.clone());
 
+        // the companion contract the pool's policies are minted from, deployed with a salt derived from the pool's
+        // and initialized by the pool with itself as minter
+        let policy_nft_salt = env.crypto().sha256(&Bytes::from(salt));
+        let policy_nft_address = env
+            .deployer()
+            .with_current_contract(policy_nft_salt)
+            .deploy(get_policy_nft_hash(&env));
+
         pool.initialize(
             &admin,
             &token,
             &oracle,
-            &symbol,
-            &external_asset,
-            &oracle_asset,
+            &assets,
             &periods_in_days,
             &volatility,
             &multiplier,
+            &Some(policy_nft_address),
         );
 
         events::deployed_pool(&env, &pool_address);
@@ -0,0 +1,240 @@
+use crate::{
+    events,
+    storage::*,
+    types::{Error, PolicyMetadata},
+};
+use soroban_sdk::{contract, contractimpl, Address, Env, String};
+
+#[contract]
+pub struct PolicyNft;
+
+pub trait Initializable {
+    /// initialize
+
+    /// Constructor function, only to be callable once
+
+    /// `initialize()` must be provided with:
+    /// `minter: Address` The insurance pool whose policies are represented by the tokens, the only address that can mint and burn them.
+    /// Requires the minter's auth, the pool initializes its token contract from its own `initialize()`:
+    /// a token contract someone else got to first can't be attached to a pool.
+    fn initialize(env: Env, minter: Address) -> Result<(), Error>;
+}
+
+pub trait Minter {
+    /// mint
+
+    /// Mints a new policy token to `to`. Minter only.
+    /// Returns the id of the token.
+    fn mint(env: Env, to: Address, metadata: PolicyMetadata) -> Result<u64, Error>;
+
+    /// burn
+
+    /// Burns the token of a policy that was paid out. Minter only.
+    fn burn(env: Env, token_id: u64) -> Result<(), Error>;
+}
+
+pub trait NonFungible {
+    /// transfer
+
+    /// Transfers `token_id` from its owner `from` to `to`.
+    fn transfer(env: Env, from: Address, to: Address, token_id: u64) -> Result<(), Error>;
+
+    /// transfer_from
+
+    /// Transfers `token_id` from its owner `from` to `to` on behalf of the owner.
+    /// `spender` must be approved for the token or be an operator of the owner.
+    fn transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        token_id: u64,
+    ) -> Result<(), Error>;
+
+    /// approve
+
+    /// Allows `approved` to transfer `token_id` on behalf of its `owner`, `None` revokes the approval.
+    /// The approval is cleared when the token is transferred.
+    fn approve(
+        env: Env,
+        owner: Address,
+        approved: Option<Address>,
+        token_id: u64,
+    ) -> Result<(), Error>;
+
+    /// set_approval_for_all
+
+    /// Allows or disallows `operator` to transfer every token of `owner`.
+    fn set_approval_for_all(env: Env, owner: Address, operator: Address, approved: bool);
+
+    /// Returns the owner of a token.
+    fn owner_of(env: Env, token_id: u64) -> Result<Address, Error>;
+
+    /// Returns the number of tokens an address owns.
+    fn balance(env: Env, owner: Address) -> u32;
+
+    /// Returns the address approved for a token, if any.
+    fn get_approved(env: Env, token_id: u64) -> Option<Address>;
+
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool;
+
+    fn name(env: Env) -> String;
+
+    fn symbol(env: Env) -> String;
+
+    /// Returns the pool, period, coverage and strike of the policy represented by a token.
+    fn token_metadata(env: Env, token_id: u64) -> Result<PolicyMetadata, Error>;
+}
+
+fn move_token(env: &Env, from: Address, to: Address, token_id: u64) {
+    write_owner(env, token_id, to.clone());
+    write_approved(env, token_id, None);
+    write_balance(env, from.clone(), read_balance(env, from.clone()) - 1);
+    write_balance(env, to.clone(), read_balance(env, to.clone()) + 1);
+
+    events::transferred(env, from, to, token_id);
+}
+
+#[contractimpl]
+impl Initializable for PolicyNft {
+    fn initialize(env: Env, minter: Address) -> Result<(), Error> {
+        minter.require_auth();
+
+        if has_minter(&env) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        put_minter(&env, minter);
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl Minter for PolicyNft {
+    fn mint(env: Env, to: Address, metadata: PolicyMetadata) -> Result<u64, Error> {
+        get_minter(&env)?.require_auth();
+
+        bump_instance(&env);
+
+        let token_id = next_id(&env);
+        write_owner(&env, token_id, to.clone());
+        write_metadata(&env, token_id, metadata);
+        write_balance(&env, to.clone(), read_balance(&env, to.clone()) + 1);
+
+        events::minted(&env, to, token_id);
+        Ok(token_id)
+    }
+
+    fn burn(env: Env, token_id: u64) -> Result<(), Error> {
+        get_minter(&env)?.require_auth();
+
+        let owner = read_owner(&env, token_id)?;
+
+        bump_instance(&env);
+
+        remove_token(&env, token_id);
+        write_balance(&env, owner.clone(), read_balance(&env, owner.clone()) - 1);
+
+        events::burned(&env, owner, token_id);
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl NonFungible for PolicyNft {
+    fn transfer(env: Env, from: Address, to: Address, token_id: u64) -> Result<(), Error> {
+        from.require_auth();
+
+        if read_owner(&env, token_id)? != from {
+            return Err(Error::NotOwner);
+        }
+
+        bump_instance(&env);
+
+        move_token(&env, from, to, token_id);
+        Ok(())
+    }
+
+    fn transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        token_id: u64,
+    ) -> Result<(), Error> {
+        spender.require_auth();
+
+        if read_owner(&env, token_id)? != from {
+            return Err(Error::NotOwner);
+        }
+
+        if read_approved(&env, token_id) != Some(spender.clone())
+            && !is_operator(&env, from.clone(), spender)
+        {
+            return Err(Error::NotApproved);
+        }
+
+        bump_instance(&env);
+
+        move_token(&env, from, to, token_id);
+        Ok(())
+    }
+
+    fn approve(
+        env: Env,
+        owner: Address,
+        approved: Option<Address>,
+        token_id: u64,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        if read_owner(&env, token_id)? != owner {
+            return Err(Error::NotOwner);
+        }
+
+        bump_instance(&env);
+
+        write_approved(&env, token_id, approved.clone());
+
+        events::approved(&env, owner, approved, token_id);
+        Ok(())
+    }
+
+    fn set_approval_for_all(env: Env, owner: Address, operator: Address, approved: bool) {
+        owner.require_auth();
+
+        bump_instance(&env);
+
+        write_operator(&env, owner.clone(), operator.clone(), approved);
+
+        events::approved_for_all(&env, owner, operator, approved);
+    }
+
+    fn owner_of(env: Env, token_id: u64) -> Result<Address, Error> {
+        read_owner(&env, token_id)
+    }
+
+    fn balance(env: Env, owner: Address) -> u32 {
+        read_balance(&env, owner)
+    }
+
+    fn get_approved(env: Env, token_id: u64) -> Option<Address> {
+        read_approved(&env, token_id)
+    }
+
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        is_operator(&env, owner, operator)
+    }
+
+    fn name(env: Env) -> String {
+        String::from_str(&env, "Insurance Policy")
+    }
+
+    fn symbol(env: Env) -> String {
+        String::from_str(&env, "POLICY")
+    }
+
+    fn token_metadata(env: Env, token_id: u64) -> Result<PolicyMetadata, Error> {
+        read_metadata(&env, token_id)
+    }
+}
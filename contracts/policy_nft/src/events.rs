@@ -0,0 +1,26 @@
+use soroban_sdk::{symbol_short, Address, Env};
+
+pub(crate) fn minted(env: &Env, to: Address, token_id: u64) {
+    let topics = (symbol_short!("mint"), to);
+    env.events().publish(topics, token_id);
+}
+
+pub(crate) fn burned(env: &Env, from: Address, token_id: u64) {
+    let topics = (symbol_short!("burn"), from);
+    env.events().publish(topics, token_id);
+}
+
+pub(crate) fn transferred(env: &Env, from: Address, to: Address, token_id: u64) {
+    let topics = (symbol_short!("transfer"), from, to);
+    env.events().publish(topics, token_id);
+}
+
+pub(crate) fn approved(env: &Env, owner: Address, approved: Option<Address>, token_id: u64) {
+    let topics = (symbol_short!("approve"), owner, token_id);
+    env.events().publish(topics, approved);
+}
+
+pub(crate) fn approved_for_all(env: &Env, owner: Address, operator: Address, approved: bool) {
+    let topics = (symbol_short!("appr_all"), owner, operator);
+    env.events().publish(topics, approved);
+}
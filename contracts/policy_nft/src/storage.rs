@@ -0,0 +1,135 @@
+use soroban_sdk::{Address, Env};
+
+use crate::{
+    types::{Error, InstanceDataKey, OperatorObject, PersistentDataKey, PolicyMetadata},
+    INSTANCE_LEDGER_LIFE, INSTANCE_LEDGER_TTL_THRESHOLD, PERSISTENT_LEDGER_LIFE,
+    PERSISTENT_LEDGER_TTL_THRESHOLD,
+};
+
+// persistent
+
+pub(crate) fn bump_persistent(e: &Env, key: &PersistentDataKey) {
+    e.storage().persistent().extend_ttl(
+        key,
+        PERSISTENT_LEDGER_TTL_THRESHOLD,
+        PERSISTENT_LEDGER_LIFE,
+    );
+}
+
+pub(crate) fn write_owner(e: &Env, token_id: u64, owner: Address) {
+    let key = PersistentDataKey::Owner(token_id);
+    e.storage().persistent().set(&key, &owner);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_owner(e: &Env, token_id: u64) -> Result<Address, Error> {
+    let key = PersistentDataKey::Owner(token_id);
+
+    if let Some(owner) = e.storage().persistent().get(&key) {
+        bump_persistent(e, &key);
+        Ok(owner)
+    } else {
+        Err(Error::NoToken)
+    }
+}
+
+pub(crate) fn write_approved(e: &Env, token_id: u64, approved: Option<Address>) {
+    let key = PersistentDataKey::Approved(token_id);
+
+    if let Some(approved) = approved {
+        e.storage().persistent().set(&key, &approved);
+        bump_persistent(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub(crate) fn read_approved(e: &Env, token_id: u64) -> Option<Address> {
+    let key = PersistentDataKey::Approved(token_id);
+    e.storage().persistent().get(&key)
+}
+
+pub(crate) fn write_metadata(e: &Env, token_id: u64, metadata: PolicyMetadata) {
+    let key = PersistentDataKey::Metadata(token_id);
+    e.storage().persistent().set(&key, &metadata);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_metadata(e: &Env, token_id: u64) -> Result<PolicyMetadata, Error> {
+    let key = PersistentDataKey::Metadata(token_id);
+    e.storage().persistent().get(&key).ok_or(Error::NoToken)
+}
+
+pub(crate) fn remove_token(e: &Env, token_id: u64) {
+    e.storage()
+        .persistent()
+        .remove(&PersistentDataKey::Owner(token_id));
+    e.storage()
+        .persistent()
+        .remove(&PersistentDataKey::Approved(token_id));
+    e.storage()
+        .persistent()
+        .remove(&PersistentDataKey::Metadata(token_id));
+}
+
+pub(crate) fn write_balance(e: &Env, owner: Address, balance: u32) {
+    let key = PersistentDataKey::Balance(owner);
+    e.storage().persistent().set(&key, &balance);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_balance(e: &Env, owner: Address) -> u32 {
+    let key = PersistentDataKey::Balance(owner);
+    e.storage().persistent().get(&key).unwrap_or(0)
+}
+
+pub(crate) fn write_operator(e: &Env, owner: Address, operator: Address, approved: bool) {
+    let key = PersistentDataKey::Operator(OperatorObject::new(owner, operator));
+
+    if approved {
+        e.storage().persistent().set(&key, &true);
+        bump_persistent(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub(crate) fn is_operator(e: &Env, owner: Address, operator: Address) -> bool {
+    let key = PersistentDataKey::Operator(OperatorObject::new(owner, operator));
+    e.storage().persistent().has(&key)
+}
+
+// instance
+
+pub(crate) fn bump_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LEDGER_TTL_THRESHOLD, INSTANCE_LEDGER_LIFE);
+}
+
+pub(crate) fn put_minter(e: &Env, minter: Address) {
+    let key = InstanceDataKey::Minter;
+    e.storage().instance().set(&key, &minter);
+}
+
+pub(crate) fn get_minter(e: &Env) -> Result<Address, Error> {
+    let key = InstanceDataKey::Minter;
+    e.storage()
+        .instance()
+        .get(&key)
+        .ok_or(Error::NotInitialized)
+}
+
+pub(crate) fn has_minter(e: &Env) -> bool {
+    let key = InstanceDataKey::Minter;
+    e.storage().instance().has(&key)
+}
+
+// ids start at 1
+pub(crate) fn next_id(e: &Env) -> u64 {
+    let key = InstanceDataKey::NextId;
+    let id = e.storage().instance().get(&key).unwrap_or(1);
+    e.storage().instance().set(&key, &(id + 1));
+
+    id
+}
@@ -0,0 +1,205 @@
+extern crate std;
+
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    Address, Env, IntoVal, Symbol,
+};
+
+use crate::{
+    contract::{PolicyNft, PolicyNftClient},
+    types::{Error, PolicyMetadata},
+};
+
+struct Setup<'a> {
+    env: Env,
+    nft: PolicyNftClient<'a>,
+    minter: Address,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let minter = Address::generate(&env);
+    let nft = PolicyNftClient::new(&env, &env.register_contract(None, PolicyNft));
+    nft.initialize(&minter);
+
+    Setup { env, nft, minter }
+}
+
+fn metadata(setup: &Setup) -> PolicyMetadata {
+    PolicyMetadata {
+        pool: setup.minter.clone(),
+        period: 1,
+        coverage: 100,
+        strike: 20,
+    }
+}
+
+fn mint(setup: &Setup, to: &Address) -> u64 {
+    setup.nft.mint(to, &metadata(setup))
+}
+
+#[test]
+fn initialize_requires_the_minter() {
+    let setup = setup();
+    assert_eq!(
+        setup.env.auths(),
+        std::vec![(
+            setup.minter.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    setup.nft.address.clone(),
+                    Symbol::new(&setup.env, "initialize"),
+                    (setup.minter.clone(),).into_val(&setup.env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+
+    let other = Address::generate(&setup.env);
+    assert_eq!(
+        setup.nft.try_initialize(&other),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn only_the_minter_mints_and_burns() {
+    let setup = setup();
+    let buyer = Address::generate(&setup.env);
+
+    let token_id = mint(&setup, &buyer);
+    assert_eq!(setup.env.auths()[0].0, setup.minter);
+    assert_eq!(setup.nft.owner_of(&token_id), buyer);
+    assert_eq!(setup.nft.balance(&buyer), 1);
+    let recorded = setup.nft.token_metadata(&token_id);
+    assert_eq!(recorded.pool, setup.minter);
+    assert_eq!(
+        (recorded.period, recorded.coverage, recorded.strike),
+        (1, 100, 20)
+    );
+    assert_eq!(mint(&setup, &buyer), token_id + 1);
+
+    setup.nft.burn(&token_id);
+    assert_eq!(setup.env.auths()[0].0, setup.minter);
+    assert_eq!(setup.nft.try_owner_of(&token_id), Err(Ok(Error::NoToken)));
+    assert_eq!(setup.nft.balance(&buyer), 1);
+    assert_eq!(setup.nft.try_burn(&token_id), Err(Ok(Error::NoToken)));
+}
+
+#[test]
+fn owners_transfer_their_tokens() {
+    let setup = setup();
+    let buyer = Address::generate(&setup.env);
+    let owner = Address::generate(&setup.env);
+    let token_id = mint(&setup, &buyer);
+
+    assert_eq!(
+        setup.nft.try_transfer(&owner, &buyer, &token_id),
+        Err(Ok(Error::NotOwner))
+    );
+
+    setup.nft.transfer(&buyer, &owner, &token_id);
+    assert_eq!(setup.env.auths()[0].0, buyer);
+    assert_eq!(setup.nft.owner_of(&token_id), owner);
+    assert_eq!(setup.nft.balance(&buyer), 0);
+    assert_eq!(setup.nft.balance(&owner), 1);
+
+    // the token now follows its new owner
+    assert_eq!(
+        setup.nft.try_transfer(&buyer, &owner, &token_id),
+        Err(Ok(Error::NotOwner))
+    );
+}
+
+#[test]
+fn approved_spenders_transfer_once() {
+    let setup = setup();
+    let buyer = Address::generate(&setup.env);
+    let spender = Address::generate(&setup.env);
+    let owner = Address::generate(&setup.env);
+    let token_id = mint(&setup, &buyer);
+
+    assert_eq!(
+        setup
+            .nft
+            .try_transfer_from(&spender, &buyer, &owner, &token_id),
+        Err(Ok(Error::NotApproved))
+    );
+    assert_eq!(
+        setup
+            .nft
+            .try_approve(&spender, &Some(spender.clone()), &token_id),
+        Err(Ok(Error::NotOwner))
+    );
+
+    setup.nft.approve(&buyer, &Some(spender.clone()), &token_id);
+    assert_eq!(setup.env.auths()[0].0, buyer);
+    assert_eq!(setup.nft.get_approved(&token_id), Some(spender.clone()));
+
+    setup.nft.transfer_from(&spender, &buyer, &owner, &token_id);
+    assert_eq!(setup.env.auths()[0].0, spender);
+    assert_eq!(setup.nft.owner_of(&token_id), owner);
+
+    // the approval was cleared by the transfer
+    assert_eq!(setup.nft.get_approved(&token_id), None);
+    assert_eq!(
+        setup
+            .nft
+            .try_transfer_from(&spender, &owner, &buyer, &token_id),
+        Err(Ok(Error::NotApproved))
+    );
+}
+
+#[test]
+fn revoked_approvals_are_refused() {
+    let setup = setup();
+    let buyer = Address::generate(&setup.env);
+    let spender = Address::generate(&setup.env);
+    let token_id = mint(&setup, &buyer);
+
+    setup.nft.approve(&buyer, &Some(spender.clone()), &token_id);
+    setup.nft.approve(&buyer, &None, &token_id);
+    assert_eq!(
+        setup
+            .nft
+            .try_transfer_from(&spender, &buyer, &spender, &token_id),
+        Err(Ok(Error::NotApproved))
+    );
+}
+
+#[test]
+fn operators_transfer_every_token_of_the_owner() {
+    let setup = setup();
+    let buyer = Address::generate(&setup.env);
+    let operator = Address::generate(&setup.env);
+    let owner = Address::generate(&setup.env);
+    let first = mint(&setup, &buyer);
+    let second = mint(&setup, &buyer);
+
+    setup.nft.set_approval_for_all(&buyer, &operator, &true);
+    assert_eq!(setup.env.auths()[0].0, buyer);
+    assert!(setup.nft.is_approved_for_all(&buyer, &operator));
+
+    setup.nft.transfer_from(&operator, &buyer, &owner, &first);
+    assert_eq!(setup.nft.owner_of(&first), owner);
+
+    // operators act for the owner, not for whoever holds the token next
+    assert_eq!(
+        setup
+            .nft
+            .try_transfer_from(&operator, &owner, &buyer, &first),
+        Err(Ok(Error::NotApproved))
+    );
+
+    setup.nft.set_approval_for_all(&buyer, &operator, &false);
+    assert!(!setup.nft.is_approved_for_all(&buyer, &operator));
+    assert_eq!(
+        setup
+            .nft
+            .try_transfer_from(&operator, &buyer, &owner, &second),
+        Err(Ok(Error::NotApproved))
+    );
+}
@@ -0,0 +1,54 @@
+use soroban_sdk::{contracterror, contracttype, Address};
+
+/// What a policy token stands for, as recorded by the pool when minting it.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PolicyMetadata {
+    pub pool: Address,
+    pub period: i32,
+    /// coverage of the policy before its deductible and co-insurance.
+    pub coverage: i128,
+    /// price the policy's triggering condition is relative to.
+    pub strike: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct OperatorObject {
+    owner: Address,
+    operator: Address,
+}
+
+impl OperatorObject {
+    pub fn new(owner: Address, operator: Address) -> Self {
+        Self { owner, operator }
+    }
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum InstanceDataKey {
+    Minter,
+    NextId,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum PersistentDataKey {
+    Owner(u64),
+    Approved(u64),
+    Metadata(u64),
+    Balance(Address),
+    Operator(OperatorObject),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 0,
+    NotInitialized = 1,
+    NoToken = 2,
+    NotOwner = 3,
+    NotApproved = 4,
+}
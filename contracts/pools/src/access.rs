@@ -0,0 +1,48 @@
+use crate::{
+    events,
+    storage::{get_admin, get_governance, is_role_member, write_role_member},
+    types::{Error, Role},
+};
+use soroban_sdk::{Address, Env};
+
+/// Whether `addr` holds `role`. The owner holds every role, unless liquidity providers govern the pool: it then only
+/// holds the roles it was granted.
+pub(crate) fn has_role(e: &Env, role: Role, addr: &Address) -> Result<bool, Error> {
+    if get_admin(e)? == *addr && (role == Role::Owner || get_governance(e).is_none()) {
+        return Ok(true);
+    }
+
+    Ok(role != Role::Owner && is_role_member(e, role, addr.clone()))
+}
+
+/// Grants `role` to `addr`, or revokes it. Ownership can't be granted.
+pub(crate) fn set_role(e: &Env, role: Role, addr: Address, member: bool) -> Result<(), Error> {
+    if role == Role::Owner {
+        return Err(Error::InvalidConfig);
+    }
+
+    write_role_member(e, role, addr.clone(), member);
+
+    events::role_updated(e, role, addr, member);
+    Ok(())
+}
+
+/// Makes sure that the pool isn't governed by its liquidity providers, for what they decide when it is.
+pub(crate) fn check_not_governed(e: &Env) -> Result<(), Error> {
+    if get_governance(e).is_some() {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(())
+}
+
+/// Makes sure that `caller` authorized the call and holds `role`.
+pub(crate) fn require_role(e: &Env, caller: &Address, role: Role) -> Result<(), Error> {
+    caller.require_auth();
+
+    if !has_role(e, role, caller)? {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use crate::{
     math::{calculate_principal_value, calculate_to_mint},
     storage::{
         get_tot_liquidity, get_tot_supply, put_tot_supply, read_balance,
-        read_matured_fees_particular, write_balance,
+        read_matured_fees_particular, record_deposit, write_balance,
     },
 };
 use soroban_sdk::{Address, Env};
@@ -18,8 +18,10 @@ pub(crate) fn mint_shares(e: &Env, to: Address, amount: i128, period: i32) {
     // add to total supply
     put_tot_supply(e, get_tot_supply(e, period) + to_mint, period);
 
-    // add to user balance
-    write_balance(e, to.clone(), read_balance(e, to, period) + to_mint, period);
+    // add to user balance, keeping what it was before for the proposals opened until now
+    let balance = read_balance(e, to.clone(), period);
+    record_deposit(e, to.clone(), balance, period);
+    write_balance(e, to, balance + to_mint, period);
 }
 
 // needs to be rewritten
@@ -1,4 +1,12 @@
-use crate::{math::actual_period, types::Error};
+use crate::{
+    math::actual_period,
+    storage::{get_version, is_paused},
+    types::{
+        CoverageCaps, Error, GovernanceConfig, PauseScope, PayoutMode, PolicyTerms, RiskParams,
+    },
+    MAX_COINSURANCE, MAX_ORACLE_RECORDS, MAX_ORACLE_SOURCES, STORAGE_VERSION,
+};
+use fixed_point_math::{FixedPoint, STROOP};
 use soroban_sdk::{token::Client, Env};
 
 /// Make sure that we're dealing with amounts > 0
@@ -9,3 +17,107 @@ pub(crate) fn check_amount_gt_0(amount: i128) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Make sure that the deductible isn't negative and that the policyholder doesn't bear (almost) the whole loss
+pub(crate) fn check_terms(terms: &PolicyTerms) -> Result<(), Error> {
+    if terms.deductible < 0 || !(0..=MAX_COINSURANCE).contains(&terms.coinsurance) {
+        return Err(Error::InvalidTerms);
+    }
+
+    Ok(())
+}
+
+/// Make sure that risk parameters can price and trigger policies: positive volatility and multiplier, a quorum the
+/// oracle sources can reach, guards within the records the pool reads and a payout growing with the price move
+pub(crate) fn check_risk_params(params: &RiskParams) -> Result<(), Error> {
+    let oracles = params.oracles.len();
+    let quorum = &params.quorum;
+
+    if params.volatility <= 0
+        || params.multiplier <= 0
+        || oracles == 0
+        || oracles > MAX_ORACLE_SOURCES
+        || quorum.min_sources == 0
+        || quorum.min_sources > oracles
+        || quorum.max_divergence < 0
+        || params.guards.max_deviation < 0
+        || params.guards.min_records > MAX_ORACLE_RECORDS
+    {
+        return Err(Error::InvalidConfig);
+    }
+
+    if let PayoutMode::Linear(config) = &params.payout {
+        if config.attachment < 0
+            || config.exhaustion <= config.attachment
+            || config.coverage_scale <= 0
+        {
+            return Err(Error::InvalidConfig);
+        }
+    }
+
+    Ok(())
+}
+
+/// Make sure that `coverage` bought for an address that already holds `address_coverage` in the period respects
+/// the pool's caps
+pub(crate) fn check_coverage_caps(
+    caps: &CoverageCaps,
+    coverage: i128,
+    address_coverage: i128,
+    tot_liquidity: i128,
+) -> Result<(), Error> {
+    if caps.max_per_policy > 0 && coverage > caps.max_per_policy {
+        return Err(Error::PolicyCapExceeded);
+    }
+
+    let address_coverage = address_coverage + coverage;
+    if caps.max_per_address > 0 && address_coverage > caps.max_per_address {
+        return Err(Error::AddressCapExceeded);
+    }
+
+    if caps.max_pool_share > 0
+        && address_coverage
+            > tot_liquidity
+                .fixed_mul_floor(caps.max_pool_share, STROOP.into())
+                .unwrap()
+    {
+        return Err(Error::PoolShareExceeded);
+    }
+
+    Ok(())
+}
+
+/// Make sure that governance settings let proposals pass
+pub(crate) fn check_governance(config: &GovernanceConfig) -> Result<(), Error> {
+    if config.voting_period == 0
+        || config.upgrade_delay == 0
+        || !(1..=STROOP as i128).contains(&config.quorum)
+    {
+        return Err(Error::InvalidConfig);
+    }
+
+    Ok(())
+}
+
+/// Make sure that `scope` isn't paused
+pub(crate) fn check_not_paused(e: &Env, scope: PauseScope) -> Result<(), Error> {
+    if !is_paused(e, scope) {
+        return Ok(());
+    }
+
+    Err(match scope {
+        PauseScope::Deposits => Error::PausedDeposits,
+        PauseScope::Subscriptions => Error::PausedSubscriptions,
+        PauseScope::Claims => Error::PausedClaims,
+        PauseScope::Withdrawals => Error::PausedWithdrawals,
+    })
+}
+
+/// Make sure that the storage was migrated to the layout this code expects
+pub(crate) fn check_version(e: &Env) -> Result<(), Error> {
+    if get_version(e) != STORAGE_VERSION {
+        return Err(Error::VersionMismatch);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use crate::{
+    checks::{check_not_paused, check_version},
+    events,
+    math::{actual_period, apply_terms, linear_payout, period_end, relative_move},
+    oracle::{last_price, price_at, prices_between},
+    policy_nft::{burn_policy, policy_owner},
+    risk::period_params,
+    storage::*,
+    token_utility::{get_token_client, transfer},
+    types::{
+        BalanceObject, Error, Insurance, ObservedRange, PauseScope, PayoutMode, PersistentDataKey,
+        TriggerMode,
+    },
+    LEDGER_CLOSE_TIME,
+};
+use fixed_point_math::{FixedPoint, STROOP};
+use soroban_sdk::{token::Client, vec, Address, Env, Vec};
+
+/// Records the current ledger's timestamp as the last one observed in the current period. Once the next period
+/// started, the last timestamp observed in a period is kept as its close.
+pub(crate) fn observe_period(e: &Env) {
+    let period = actual_period(e);
+
+    if let Some((observed_period, timestamp)) = get_last_observed(e) {
+        if observed_period != period {
+            write_period_close(e, timestamp, observed_period);
+        }
+    }
+
+    put_last_observed(e, period, e.ledger().timestamp());
+}
+
+// the timestamp of the last ledger of `period`, which is over. When the pool wasn't used in the last ledgers of the
+// period, it's estimated from the current ledger, assuming the usual close time of ledgers since the period ended.
+fn period_close(e: &Env, period: i32) -> u64 {
+    let observed = match get_last_observed(e) {
+        Some((observed_period, timestamp)) if observed_period == period => Some(timestamp),
+        _ => read_period_close(e, period),
+    };
+
+    let ledgers_since_end = (e.ledger().sequence() as i32 - period_end(e, period)).max(0) as u64;
+    let estimated = e
+        .ledger()
+        .timestamp()
+        .saturating_sub(ledgers_since_end * LEDGER_CLOSE_TIME);
+
+    observed.map_or(estimated, |observed| observed.max(estimated))
+}
+
+/// The latest timestamp whose prices count for a policy of `period`: now while the period is running, the end of the
+/// period during its grace window.
+fn evaluation_cutoff(e: &Env, period: i32) -> Result<u64, Error> {
+    let current_period = actual_period(e);
+    if period == current_period {
+        return Ok(e.ledger().timestamp());
+    }
+
+    let end = period_end(e, period);
+    if period + 1 != current_period
+        || e.ledger().sequence() as i32 > end + get_claim_grace(e) as i32
+    {
+        return Err(Error::ClaimWindowClosed);
+    }
+
+    // prices recorded after the period closed are not taken into account
+    Ok(period_close(e, period))
+}
+
+// the extremes of the prices recorded since `refund` was bought, read on from where the last reading stopped
+fn observed_range(
+    e: &Env,
+    holder: &Address,
+    refund: &Insurance,
+    period: i32,
+    cutoff: u64,
+) -> Result<ObservedRange, Error> {
+    let stored = read_observed_range(e, holder.clone(), period);
+    let from = stored
+        .as_ref()
+        .map_or(refund.timestamp, |range| range.until + 1);
+
+    let (prices, until) = prices_between(e, period, from, cutoff)?;
+
+    // moves are measured from the strike, which is where the range starts
+    let mut range = stored.unwrap_or(ObservedRange {
+        low: refund.price,
+        high: refund.price,
+        until,
+    });
+
+    for price in prices.iter() {
+        range.low = range.low.min(price);
+        range.high = range.high.max(price);
+    }
+    range.until = range.until.max(until);
+
+    Ok(range)
+}
+
+/// Reads the oracle prices recorded for the policy `holder` was sold for `period` since the last reading, and keeps
+/// their extremes. Only needed in `Drawdown` mode, when the policy was held for longer than `MAX_ORACLE_RECORDS`
+/// oracle timeframes.
+/// Returns the timestamp the prices were read until.
+pub(crate) fn record_prices(e: &Env, holder: Address, period: i32) -> Result<u64, Error> {
+    check_version(e)?;
+
+    observe_period(e);
+
+    if period_params(e, period)?.trigger != TriggerMode::Drawdown {
+        return Err(Error::InvalidConfig);
+    }
+
+    let refund = read_refund_particular(e, holder.clone(), period).ok_or(Error::NoInsurance)?;
+    let cutoff = evaluation_cutoff(e, period)?;
+
+    let range = observed_range(e, &holder, &refund, period, cutoff)?;
+    write_observed_range(e, holder, &range, period);
+
+    Ok(range.until)
+}
+
+/// The prices a policy of `period` is evaluated against, according to the pool's trigger mode, and whether they
+/// cover the whole time the policy was held.
+fn observed_prices(
+    e: &Env,
+    holder: &Address,
+    refund: &Insurance,
+    period: i32,
+    trigger: TriggerMode,
+) -> Result<(Vec<i128>, bool), Error> {
+    let cutoff = evaluation_cutoff(e, period)?;
+
+    match trigger {
+        TriggerMode::Spot => {
+            let price = if period == actual_period(e) {
+                last_price(e)?
+            } else {
+                price_at(e, period, cutoff)?
+            };
+
+            Ok((vec![e, price], true))
+        }
+        TriggerMode::Drawdown => {
+            // the largest moves in both directions are the extremes of the range
+            let range = observed_range(e, holder, refund, period, cutoff)?;
+
+            Ok((vec![e, range.low, range.high], range.until >= cutoff))
+        }
+    }
+}
+
+/// The amount `refund` pays out if claimed now before its deductible and co-insurance.
+fn gross_claimable(
+    e: &Env,
+    holder: &Address,
+    refund: &Insurance,
+    period: i32,
+) -> Result<i128, Error> {
+    // policies are triggered and paid the way the period they were sold in was priced
+    let params = period_params(e, period)?;
+    let (prices, complete) = observed_prices(e, holder, refund, period, params.trigger)?;
+
+    let gross = match params.payout {
+        PayoutMode::Binary => {
+            let volatility = params.volatility;
+            let outside = |price: i128| {
+                refund.price + volatility < price || refund.price - volatility > price
+            };
+
+            if prices.iter().any(outside) {
+                refund.amount
+            } else {
+                0
+            }
+        }
+        PayoutMode::Linear(config) => {
+            let mut largest_move = 0;
+            for price in prices.iter() {
+                let price_move = relative_move(refund.price, price).ok_or(Error::NoPrice)?;
+                largest_move = largest_move.max(price_move);
+            }
+
+            linear_payout(
+                refund.amount,
+                largest_move,
+                config.attachment,
+                config.exhaustion,
+            )
+        }
+    };
+
+    // prices not read yet could still raise the payout
+    if !complete && gross < refund.amount {
+        return Err(Error::IncompleteHistory);
+    }
+
+    Ok(gross)
+}
+
+/// The amount `refund` pays out if claimed now, zero if the triggering condition doesn't verify.
+/// Fails with `IncompleteHistory` when `record_prices()` must be called first.
+pub(crate) fn claimable_amount(
+    e: &Env,
+    holder: &Address,
+    refund: &Insurance,
+    period: i32,
+) -> Result<i128, Error> {
+    Ok(apply_terms(
+        gross_claimable(e, holder, refund, period)?,
+        &refund.terms,
+    ))
+}
+
+/// The part of `amount` the pool can pay for a policy of `period` given the liquidity it actually holds.
+/// In pro-rata mode every claimant of an insolvent period receives the same share of its claim, otherwise the claim
+/// fails.
+fn solvent_amount(
+    e: &Env,
+    token_client: &Client,
+    amount: i128,
+    period: i32,
+) -> Result<i128, Error> {
+    let held = token_client.balance(&e.current_contract_address()) - get_reserve_fund(e);
+    let available = get_tot_liquidity(e, period).min(held).max(0);
+    let refund_global = read_refund_global(e, period);
+
+    if refund_global <= available {
+        return Ok(amount);
+    }
+
+    if !get_pro_rata(e) {
+        if amount > available {
+            return Err(Error::Insolvent);
+        }
+
+        return Ok(amount);
+    }
+
+    // the share is computed on the whole reserved coverage so that claimants are paid alike whatever their order
+    Ok(amount
+        .fixed_mul_floor(available, refund_global)
+        .unwrap()
+        .min(available))
+}
+
+/// Pays the policy `holder` was sold for `period` if the triggering condition verifies. The payout goes to the owner
+/// of the policy's token when it was minted as an NFT, who must authorize the claim unless a `keeper` settles it.
+/// When the claim is settled by a `keeper`, part of the payout goes to them as a bounty. Keepers can only settle a
+/// partial payout once the period is over.
+/// Returns who was paid and the amount they received.
+pub(crate) fn execute_claim(
+    e: &Env,
+    holder: Address,
+    period: i32,
+    keeper: Option<Address>,
+) -> Result<(Address, i128), Error> {
+    check_not_paused(e, PauseScope::Claims)?;
+    check_version(e)?;
+
+    observe_period(e);
+
+    // check if there is an available possible refund in the period
+    let refund = read_refund_particular(e, holder.clone(), period).ok_or(Error::NoInsurance)?;
+    let recipient = policy_owner(e, holder.clone(), refund.token);
+
+    // once the policy's token was sold, only its new owner can decide to take the payout
+    if keeper.is_none() {
+        recipient.require_auth();
+    }
+
+    let gross = gross_claimable(e, &holder, &refund, period)?;
+    let claimed = apply_terms(gross, &refund.terms);
+    if claimed == 0 {
+        return Err(Error::UnmetCondition);
+    }
+
+    // a partial payout could still grow until the period ends, only the holder decides to take it early
+    if keeper.is_some() && gross < refund.amount && period == actual_period(e) {
+        return Err(Error::Unauthorized);
+    }
+
+    let token_client = get_token_client(e);
+
+    let amount = solvent_amount(e, &token_client, claimed, period)?;
+    if amount < claimed {
+        // the recipient may hold several policies of the period through their tokens
+        let shortfall = read_shortfall(e, recipient.clone(), period) + claimed - amount;
+        write_shortfall(e, recipient.clone(), shortfall, period);
+        events::shortfall(e, recipient.clone(), claimed - amount, period);
+    }
+
+    let bounty = if let Some(keeper) = keeper {
+        let bounty = amount
+            .fixed_mul_floor(get_keeper_bounty(e), STROOP.into())
+            .unwrap();
+
+        if bounty > 0 {
+            transfer(e, &token_client, &keeper, &bounty);
+            events::keeper_bounty(e, keeper, bounty, period);
+        }
+
+        bounty
+    } else {
+        0
+    };
+
+    let payout = amount - bounty;
+    transfer(e, &token_client, &recipient, &payout);
+
+    let tot_liquidity = get_tot_liquidity(e, period);
+    let refund_global = read_refund_global(e, period);
+
+    // the whole reserved coverage is released, only what was paid leaves the pool's liquidity
+    write_refund_global(
+        e,
+        refund_global - apply_terms(refund.amount, &refund.terms),
+        period,
+    );
+    put_tot_liquidity(e, tot_liquidity - amount, period);
+    e.storage()
+        .persistent()
+        .remove(&PersistentDataKey::RefundParticular(BalanceObject::new(
+            holder.clone(),
+            period,
+        )));
+    remove_observed_range(e, holder, period);
+    burn_policy(e, refund.token);
+
+    Ok((recipient, payout))
+}
+
+/// Pays `holder` what the reserve fund can cover of the shortfall left by the haircuts on the payouts it received for
+/// `period`.
+/// Returns the amount paid.
+pub(crate) fn execute_shortfall_claim(
+    e: &Env,
+    holder: Address,
+    period: i32,
+) -> Result<i128, Error> {
+    check_not_paused(e, PauseScope::Claims)?;
+    check_version(e)?;
+
+    let shortfall = read_shortfall(e, holder.clone(), period);
+    let reserve = get_reserve_fund(e);
+    if shortfall == 0 || reserve == 0 {
+        return Err(Error::NoShortfall);
+    }
+
+    let paid = shortfall.min(reserve);
+    transfer(e, &get_token_client(e), &holder, &paid);
+
+    put_reserve_fund(e, reserve - paid);
+    write_shortfall(e, holder, shortfall - paid, period);
+
+    Ok(paid)
+}
@@ -1,16 +1,36 @@
 use crate::{
+    access::{check_not_governed, has_role, require_role, set_role},
     balance::{burn_shares, get_withdrawable_amount, mint_shares},
-    checks::check_amount_gt_0,
+    checks::{check_amount_gt_0, check_governance, check_not_paused, check_terms, check_version},
+    claims::{
+        claimable_amount, execute_claim, execute_shortfall_claim, observe_period, record_prices,
+    },
     events,
-    math::{actual_period, calculate_principal_value, calculate_refund, find_x},
-    reflector,
-    rewards::{pay_matured, update_fee_per_share_universal, update_rewards},
+    governance::{cast_vote, create_proposal, execute_proposal},
+    math::{actual_period, calculate_principal_value, period_end},
+    migrations::migrate,
+    oracle::{last_price, source_prices},
+    pause::{pause_scopes, unpause_scope},
+    policy_nft::{init_policy_nft, policy_owner},
+    premiums::{convert_premiums, has_foreign_premiums, premium_value},
+    price_source::query_decimals,
+    pricing::coverage_scale,
+    rewards::{pay_matured, update_rewards},
+    risk::{period_params, propose_params, scheduled_params},
     storage::*,
+    subscription::{open_period, subscribe_batch, subscribe_policy, subscription_open_until},
     token_utility::{get_token_client, transfer, transfer_in_pool},
-    types::{BalanceObject, Error, InstanceDataKey, Insurance, PersistentDataKey},
-    DAY_IN_LEDGERS,
+    types::{
+        AssetPair, CoverageCaps, Error, GovernanceConfig, Insurance, OracleGuards, OracleKind,
+        OracleQuorum, OracleSource, PauseScope, PayoutMode, PendingRiskParams, PolicyRef,
+        PolicyTerms, PremiumToken, PriceInfo, PricingModel, Proposal, ProposalAction, QuoteAsset,
+        RiskParams, Role, StrikeMode, SubscriptionCutoff, TrackedAsset, TriggerMode,
+    },
+    DAY_IN_LEDGERS, MAX_KEEPER_BOUNTY, MAX_PREMIUM_SPREAD, MAX_REFERRAL_COMMISSION,
+    STORAGE_VERSION,
 };
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, BytesN, Env, Symbol};
+use fixed_point_math::STROOP;
+use soroban_sdk::{contract, contractimpl, symbol_short, Address, BytesN, Env, Vec};
 
 #[contract]
 pub struct Pool;
@@ -18,7 +38,100 @@ pub struct Pool;
 pub trait SubscribeInsurance {
     fn subscribe(e: Env, initiator: Address, amount: i128) -> Result<(), Error>;
 
+    /// subscribe_for
+
+    /// Buys a policy for the current period on behalf of someone else.
+
+    /// `subscribe_for()` must be provided with:
+    /// `payer: Address` The address authorizing the subscription and paying the premium.
+    /// `beneficiary: Address` The address holding the policy: it receives the payouts and is the only one who can claim.
+    /// `amount: i128` The premium.
+    /// `referrer: Option<Address>` The distribution partner that brought the sale, if any, registered with
+    /// `set_referrer()`. It's credited the pool's referral commission out of the premium.
+    fn subscribe_for(
+        e: Env,
+        payer: Address,
+        beneficiary: Address,
+        amount: i128,
+        referrer: Option<Address>,
+    ) -> Result<(), Error>;
+
+    /// subscribe_batch
+
+    /// Buys a policy for the current period for each beneficiary in `entries`, paired with its premium, with the pool's
+    /// default terms. `payer` pays all the premiums in a single transfer. The batch fails as a whole if the pool
+    /// can't cover every policy, or if any beneficiary is listed twice or already holds a policy.
+    fn subscribe_batch(e: Env, payer: Address, entries: Vec<(Address, i128)>) -> Result<(), Error>;
+
+    /// subscribe_with_token
+
+    /// Same as `subscribe_for()`, with the premium paid in one of the tokens the pool accepts besides its own.
+    /// `amount` of `token` is converted to the pool's token at the oracle rate, net of the token's spread.
+    fn subscribe_with_token(
+        e: Env,
+        payer: Address,
+        beneficiary: Address,
+        token: Address,
+        amount: i128,
+        referrer: Option<Address>,
+    ) -> Result<(), Error>;
+
+    /// subscribe_with_terms
+
+    /// Same as `subscribe()`, with a deductible and co-insurance at least as high as the pool's default terms.
+    /// The policyholder bears part of the loss, so the premium buys a larger coverage, although the most the policy
+    /// pays is lower than without the terms.
+    fn subscribe_with_terms(
+        e: Env,
+        initiator: Address,
+        amount: i128,
+        terms: PolicyTerms,
+    ) -> Result<(), Error>;
+
+    /// claim_reward
+
+    /// Claims the policy `claimant` was sold for the current period. When the policy was minted as an NFT, the payout
+    /// goes to the current owner of the token, who must authorize the claim.
     fn claim_reward(env: Env, claimant: Address) -> Result<(), Error>;
+
+    /// settle_claim
+
+    /// Pays out `policyholder`'s policy for the current period if the triggering condition verifies.
+    /// This function may be called by anyone: the `keeper` receives the configured bounty out of the payout.
+    /// In `Linear` payout mode, a policy that doesn't pay its whole coverage yet can only be settled by a keeper once
+    /// the period is over, through `settle_previous()`: earlier settlements fail with `Unauthorized`.
+
+    /// `settle_claim()` must be provided with:
+    /// `keeper: Address` The address settling the claim and receiving the bounty.
+    /// `policyholder: Address` The address holding the policy.
+    fn settle_claim(env: Env, keeper: Address, policyholder: Address) -> Result<(), Error>;
+
+    /// claim_previous
+
+    /// Claims the policy `claimant` held in the previous period, during the grace window following its end.
+    /// The triggering condition is checked against the oracle prices recorded until the last time the pool was used in
+    /// that period, see `checkpoint()`.
+    fn claim_previous(env: Env, claimant: Address) -> Result<(), Error>;
+
+    /// settle_previous
+
+    /// Same as `settle_claim()`, for a policy of the previous period still within its grace window.
+    fn settle_previous(env: Env, keeper: Address, policyholder: Address) -> Result<(), Error>;
+
+    /// claim_policy
+
+    /// Claims the policy represented by `token_id` for the current owner of the token, who receives the payout.
+    /// Policies of the previous period can be claimed during its grace window.
+    fn claim_policy(env: Env, claimant: Address, token_id: u64) -> Result<(), Error>;
+
+    /// record_prices
+
+    /// Reads the oracle prices recorded since the last reading for `policyholder`'s policy of `period`, and keeps the
+    /// lowest and highest. This function may be called by anyone.
+    /// In `Drawdown` mode each reading covers at most 20 oracle timeframes: claims on a policy held for longer fail
+    /// with `IncompleteHistory` until its prices were recorded up to the last one. Returns the timestamp the prices
+    /// were read until.
+    fn record_prices(env: Env, policyholder: Address, period: i32) -> Result<u64, Error>;
 }
 
 pub trait Vault {
@@ -32,173 +145,969 @@ pub trait Vault {
     /// Updates the matured rewards for a certain user `addr`
     /// This function may be called by anyone.
 
-    /// `update_fee_rewards()` must be provided with:
-    /// `addr: Address` The address that is udpating its fee rewards.
-    fn update_fee_rewards(e: Env, addr: Address, period: i32) -> Result<(), Error>;
+    /// `update_fee_rewards()` must be provided with:
+    /// `addr: Address` The address that is udpating its fee rewards.
+    fn update_fee_rewards(e: Env, addr: Address, period: i32) -> Result<(), Error>;
+
+    /// withdraw_matured
+
+    /// Allows a certain user `addr` to withdraw the matured fees.
+    /// Before calling `withdraw_matured()` the user should call `update_fee_rewards`.
+    /// If not, the matured fees that were not updated will not be lost, just not included in the payment.
+
+    /// `withdraw_matured()` must be provided with:
+    /// `addr: Address` The address that is withdrawing its fee rewards.
+    fn withdraw_matured(e: Env, addr: Address, period: i32) -> Result<(), Error>;
+
+    /// withdraw
+
+    /// Allows to withdraw liquidity from the pool by burning liquidity provider shares.
+
+    /// withdraw() must be provided with:
+    /// `addr: Address` Address of the liquidity provider
+    /// `amount: i28` Amount of shares that are being withdrawn
+    fn withdraw(env: Env, addr: Address, period: i32) -> Result<(), Error>;
+
+    /// Returns the amount of shares that an address holds.
+    fn shares(e: Env, addr: Address, period: i32) -> i128;
+
+    /// Returns the amount of matured fees for an address.
+    fn matured(env: Env, addr: Address, period: i32) -> i128;
+
+    fn withdrawable_amount(env: Env, addr: Address, period: i32) -> i128;
+}
+
+pub trait Referral {
+    /// set_referrer
+
+    /// Registers `referrer` as a distribution partner that can be credited referral commissions, or removes it with
+    /// `registered` false. Subscriptions referred by an unregistered address fail with `InvalidReferrer`.
+    /// Treasury only.
+    fn set_referrer(
+        env: Env,
+        caller: Address,
+        referrer: Address,
+        registered: bool,
+    ) -> Result<(), Error>;
+
+    /// Returns whether `addr` is a registered referrer.
+    fn is_referrer(env: Env, addr: Address) -> bool;
+
+    /// Returns the commissions credited to `addr` that weren't withdrawn yet.
+    fn referral_earnings(env: Env, addr: Address) -> i128;
+
+    /// withdraw_referral_earnings
+
+    /// Pays `addr` the commissions it was credited for the subscriptions it referred.
+    fn withdraw_referral_earnings(env: Env, addr: Address) -> Result<(), Error>;
+}
+
+pub trait Premiums {
+    /// Returns what `amount` of `token` is worth as a premium, in the pool's token.
+    fn premium_quote(env: Env, token: Address, amount: i128) -> Result<i128, Error>;
+
+    /// Returns the premiums of `period` collected in `token` that weren't converted yet.
+    fn foreign_premiums(env: Env, token: Address, period: i32) -> i128;
+
+    /// convert_premiums
+
+    /// Sells `amount` of the premiums of `period` collected in `token` to `buyer`, who pays their value in the pool's
+    /// token at the rate and spread premiums are accepted at. The payment is credited as fees to the liquidity
+    /// providers of `period`, who can't withdraw it before all of its premiums are converted. This function may be
+    /// called by anyone.
+    fn convert_premiums(
+        env: Env,
+        buyer: Address,
+        token: Address,
+        period: i32,
+        amount: i128,
+    ) -> Result<(), Error>;
+}
+
+pub trait Reserve {
+    /// fund_reserve
+
+    /// Adds `amount` to the reserve fund used to top up the shortfalls of haircut payouts. The reserve doesn't
+    /// belong to liquidity providers and can't be withdrawn.
+    fn fund_reserve(env: Env, from: Address, amount: i128) -> Result<(), Error>;
+
+    /// claim_shortfall
+
+    /// Pays `claimant` what the reserve fund can cover of the shortfalls left on the policies of `period` it was paid
+    /// for.
+    fn claim_shortfall(env: Env, claimant: Address, period: i32) -> Result<(), Error>;
+
+    /// Returns what is still owed to an address for the policies of `period` it was paid for.
+    fn shortfall(env: Env, addr: Address, period: i32) -> i128;
+
+    /// Returns the balance of the reserve fund.
+    fn reserve_fund(env: Env) -> i128;
+}
+
+pub trait Initializable {
+    /// initialize
+
+    /// Constructor function, only to be callable once
+
+    /// `initialize()` must be provided with:
+    /// `token_id: Address` The token used to manage liquidity in the pool (to subsribe insurances, pay premiums etc), and can be different from the asset pair tracked
+    /// `assets: AssetPair` The asset followed by the pool and, for pair pools, the asset it is priced in
+    /// 'volatility': the amount the asset has to move (+/-) with respect to the base to pay out premiums, in prices normalized to 7 decimals
+    /// (the oracle's decimals are read and stored at initialization)
+    /// 'multiplier': insurance premiums are calculated with the following formula: premium = amount + (amount * multiplier / time_until_end_of_period); the greater the multiplier, the higher the premium
+    /// `policy_nft: Option<Address>` The contract policies are minted from as NFTs, initialized by the pool with itself as minter
+    fn initialize(
+        env: Env,
+        admin: Address,
+        token: Address,
+        oracle: Address,
+        assets: AssetPair,
+        periods_in_days: i32,
+        volatility: i128,
+        multiplier: i32,
+        policy_nft: Option<Address>,
+    ) -> Result<(), Error>;
+}
+
+pub trait AccessControl {
+    /// grant_role
+
+    /// Grants `role` to `addr`. Owner only, through a proposal when liquidity providers govern the pool.
+    /// Ownership is transferred through `propose_admin()` and `accept_admin()`.
+    fn grant_role(env: Env, caller: Address, role: Role, addr: Address) -> Result<(), Error>;
+
+    /// revoke_role
+
+    /// Revokes `role` from `addr`. Owner only.
+    fn revoke_role(env: Env, caller: Address, role: Role, addr: Address) -> Result<(), Error>;
+
+    /// Returns whether `addr` holds `role`. The owner holds every role, unless liquidity providers govern the pool.
+    fn has_role(env: Env, role: Role, addr: Address) -> Result<bool, Error>;
+
+    /// propose_admin
+
+    /// Proposes `new_admin` as the next owner of the pool. Owner only.
+    /// The transfer happens once `new_admin` accepts it.
+    fn propose_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), Error>;
+
+    /// accept_admin
+
+    /// Completes the ownership transfer, must be authorized by the proposed admin.
+    fn accept_admin(env: Env) -> Result<(), Error>;
+
+    fn admin(env: Env) -> Result<Address, Error>;
+
+    fn pending_admin(env: Env) -> Option<Address>;
+}
+
+pub trait Governance {
+    /// set_governance
+
+    /// Lets the liquidity providers of the pool vote on proposals, or stops accepting them with `None`.
+    /// `voting_period` is the number of ledgers a proposal is open for votes, `quorum` the share of the LP shares
+    /// voting on it, with 7 decimals, that must vote. Approved upgrades are carried out `upgrade_delay` ledgers after
+    /// their first execution, which must leave liquidity providers time to leave. Owner only, and only until
+    /// governance is enabled: it's then changed through proposals. While liquidity providers govern the pool, the
+    /// owner can't upgrade it, nor grant roles, and only holds the roles it was granted before.
+    fn set_governance(
+        env: Env,
+        caller: Address,
+        config: Option<GovernanceConfig>,
+    ) -> Result<(), Error>;
+
+    /// propose
+
+    /// Proposes `action` to the liquidity providers of the current period, `proposer` must be one of them.
+    /// Returns the id of the proposal.
+    fn propose(env: Env, proposer: Address, action: ProposalAction) -> Result<u32, Error>;
+
+    /// vote
+
+    /// Votes for or against proposal `proposal_id` with the LP shares the voter held in its period when it was created.
+    fn vote(env: Env, voter: Address, proposal_id: u32, support: bool) -> Result<(), Error>;
+
+    /// execute
+
+    /// Executes an approved proposal once its voting period ended. Anyone can call it.
+    /// Upgrades, and unpauses when the pool has an unpause delay, must be executed a second time once their delay
+    /// elapsed.
+    fn execute(env: Env, proposal_id: u32) -> Result<(), Error>;
+
+    fn governance(env: Env) -> Option<GovernanceConfig>;
+
+    fn proposal(env: Env, proposal_id: u32) -> Option<Proposal>;
+
+    fn has_voted(env: Env, proposal_id: u32, voter: Address) -> bool;
+}
+
+pub trait Pausable {
+    /// pause
+
+    /// Pauses each operation in `scopes` until it's unpaused. Pauser only.
+    /// Pausing a scope cancels its scheduled unpause, if any.
+    fn pause(env: Env, caller: Address, scopes: Vec<PauseScope>) -> Result<(), Error>;
+
+    /// unpause
+
+    /// Unpauses `scope`. Pauser only.
+    /// When an unpause delay is set, the first call schedules the unpause and a call once the delay elapsed executes it.
+    fn unpause(env: Env, caller: Address, scope: PauseScope) -> Result<(), Error>;
+
+    /// set_unpause_delay
+
+    /// Sets the number of ledgers between the request to unpause a scope and the moment it can be unpaused. Owner only.
+    fn set_unpause_delay(env: Env, caller: Address, ledgers: u32) -> Result<(), Error>;
+
+    fn paused(env: Env, scope: PauseScope) -> bool;
+
+    /// Returns the ledger from which a scheduled unpause can be executed.
+    fn unpause_at(env: Env, scope: PauseScope) -> Option<u32>;
+}
+
+pub trait Configurable {
+    /// set_pricing_model
+
+    /// Selects how premiums are converted into coverage for new policies. Risk manager only.
+
+    /// `set_pricing_model()` must be provided with:
+    /// `model: PricingModel` either the fixed `multiplier` or a realized-volatility model that scales it
+    /// with the oracle's recent price history.
+    fn set_pricing_model(env: Env, caller: Address, model: PricingModel) -> Result<(), Error>;
+
+    /// set_keeper_bounty
+
+    /// Sets the share of a payout (7 decimals, at most 10%) that goes to whoever settles a claim through `settle_claim`. Treasury only.
+    fn set_keeper_bounty(env: Env, caller: Address, bounty: i128) -> Result<(), Error>;
+
+    /// set_claim_grace
+
+    /// Sets the number of ledgers after the end of a period during which its policies can still be claimed. Risk manager only.
+    fn set_claim_grace(env: Env, caller: Address, ledgers: u32) -> Result<(), Error>;
+
+    /// set_trigger_mode
+
+    /// Selects whether claims are checked against the spot price only, or against every price recorded by the oracle
+    /// since the policy was purchased. Risk manager only.
+    /// Policies keep the mode of the period they were sold in: the change is scheduled like `propose_risk_params()`,
+    /// on top of the pending risk parameters.
+    fn set_trigger_mode(env: Env, caller: Address, mode: TriggerMode) -> Result<(), Error>;
+
+    /// set_oracle_guards
+
+    /// Sets the checks oracle prices must pass before being used to price a policy or trigger a payout:
+    /// maximum age of the last price, maximum deviation between the last two records and minimum number of records
+    /// the feed must hold. Risk manager only.
+    /// The guards are part of the risk parameters: the change is made on top of the pending ones and scheduled like
+    /// `propose_risk_params()`.
+    fn set_oracle_guards(env: Env, caller: Address, guards: OracleGuards) -> Result<(), Error>;
+
+    /// set_oracles
+
+    /// Sets the oracle sources of the pool and how their prices are aggregated. Risk manager only.
+
+    /// `set_oracles()` must be provided with:
+    /// `oracles: Vec<OracleSource>` oracle addresses and their provider interface, in fallback order
+    /// (history queries use the first that answers).
+    /// `quorum: OracleQuorum` the aggregation mode, the minimum number of valid sources and the tolerated divergence.
+    /// The sources apply from the period `propose_risk_params()` would schedule them for.
+    fn set_oracles(
+        env: Env,
+        caller: Address,
+        oracles: Vec<OracleSource>,
+        quorum: OracleQuorum,
+    ) -> Result<(), Error>;
+
+    /// set_payout_mode
+
+    /// Selects whether triggered policies pay their whole coverage, or a share growing linearly with the price move
+    /// between an attachment and an exhaustion point. Risk manager only.
+    /// Like the trigger mode, it's scheduled with the risk parameters so that live policies keep their payout.
+    fn set_payout_mode(env: Env, caller: Address, mode: PayoutMode) -> Result<(), Error>;
+
+    /// set_policy_terms
+
+    /// Sets the deductible and co-insurance applied to the policies sold through `subscribe()`, and the minimum ones
+    /// for `subscribe_with_terms()`. Risk manager only.
+    fn set_policy_terms(env: Env, caller: Address, terms: PolicyTerms) -> Result<(), Error>;
+
+    /// set_coverage_caps
+
+    /// Sets the maximum coverage of a single policy, the maximum coverage an address can hold in a period and the
+    /// maximum share of the period's liquidity it can hold coverage for, sponsored policies counting towards their
+    /// beneficiary's. Zero disables a cap. Risk manager only.
+    fn set_coverage_caps(env: Env, caller: Address, caps: CoverageCaps) -> Result<(), Error>;
+
+    /// set_pro_rata
+
+    /// When enabled, claims of a period whose reserved coverage exceeds the liquidity the pool holds are paid
+    /// pro-rata and the unpaid part is recorded as a shortfall. When disabled, such claims fail with `Insolvent`
+    /// once the liquidity can't cover them. Risk manager only.
+    fn set_pro_rata(env: Env, caller: Address, enabled: bool) -> Result<(), Error>;
+
+    /// set_subscription_cutoff
+
+    /// Sets how long before the end of a period, in ledgers or as a share of the period, subscriptions close.
+    /// Risk manager only.
+    fn set_subscription_cutoff(
+        env: Env,
+        caller: Address,
+        cutoff: SubscriptionCutoff,
+    ) -> Result<(), Error>;
+
+    /// set_strike_mode
+
+    /// Selects whether policies trigger relative to the price at purchase time, or to a reference price recorded once
+    /// per period. In the latter case, subscriptions are refused once the price already moved more than the given
+    /// share of the triggering threshold. Risk manager only.
+    fn set_strike_mode(env: Env, caller: Address, mode: StrikeMode) -> Result<(), Error>;
+
+    /// set_referral_commission
+
+    /// Sets the share of the premium (7 decimals, at most 20%) credited to the referrer of a subscription instead of
+    /// liquidity providers. Treasury only.
+    fn set_referral_commission(env: Env, caller: Address, commission: i128) -> Result<(), Error>;
+
+    /// set_premium_tokens
+
+    /// Sets the tokens premiums can be paid in besides the pool's token. Treasury only.
+
+    /// `set_premium_tokens()` must be provided with:
+    /// `token_asset: QuoteAsset` the pool's token, as understood by its oracles.
+    /// `tokens: Vec<PremiumToken>` the accepted tokens, their oracle assets and the spread (7 decimals, at most 10%)
+    /// withheld when converting them.
+    fn set_premium_tokens(
+        env: Env,
+        caller: Address,
+        token_asset: QuoteAsset,
+        tokens: Vec<PremiumToken>,
+    ) -> Result<(), Error>;
+
+    /// propose_risk_params
+
+    /// Schedules new volatility, multiplier, symbol, oracle sources, trigger and payout modes. They apply from the
+    /// first period starting once the pool's minimum delay elapsed, periods already started keep the parameters they
+    /// were priced with.
+    /// A new proposal replaces the pending one. Risk manager only.
+    /// Returns the period the parameters will apply from.
+    fn propose_risk_params(env: Env, caller: Address, params: RiskParams) -> Result<i32, Error>;
+
+    /// cancel_risk_params
+
+    /// Cancels the pending risk parameters. Risk manager only.
+    fn cancel_risk_params(env: Env, caller: Address) -> Result<(), Error>;
+
+    /// set_risk_delay
+
+    /// Sets the minimum number of ledgers between the proposal of risk parameters and the start of the period they
+    /// apply to. Owner only.
+    fn set_risk_delay(env: Env, caller: Address, ledgers: u32) -> Result<(), Error>;
+}
+
+#[contractimpl]
+impl Pool {
+    pub fn glob(e: Env) -> (i128, i128, i128, i128) {
+        (
+            read_refund_global(&e, actual_period(&e)),
+            get_tot_liquidity(&e, actual_period(&e)),
+            get_fee_per_share_universal(&e, actual_period(&e)),
+            get_tot_supply(&e, actual_period(&e)), // number of liquidity provider shares
+        )
+    }
+
+    pub fn particular(e: Env, user: Address) -> (i128, i128, i128, Option<Insurance>) {
+        (
+            read_balance(&e, user.clone(), actual_period(&e)), // lp shares for a user
+            read_fee_per_share_particular(&e, user.clone(), actual_period(&e)),
+            read_matured_fees_particular(&e, user.clone(), actual_period(&e)),
+            read_refund_particular(&e, user.clone(), actual_period(&e)),
+        )
+    }
+
+    // aggregated price, normalized to 7 decimals
+    pub fn get_price(e: Env) -> Option<i128> {
+        last_price(&e).ok()
+    }
+
+    // last price of each oracle source, raw and normalized
+    pub fn source_prices(e: Env) -> Result<Vec<PriceInfo>, Error> {
+        source_prices(&e)
+    }
+
+    pub fn fpsu(e: Env) -> i128 {
+        get_fee_per_share_universal(&e, actual_period(&e))
+    }
+
+    pub fn fpsp(e: Env, user: Address) -> i128 {
+        read_fee_per_share_particular(&e, user, actual_period(&e))
+    }
+
+    pub fn read_current_period(e: Env) -> i32 {
+        actual_period(&e)
+    }
+
+    // amount `addr`'s policy for the current period would pay out if claimed now
+    pub fn claimable_now(e: Env, addr: Address) -> Result<i128, Error> {
+        let current_period = actual_period(&e);
+        let refund =
+            read_refund_particular(&e, addr.clone(), current_period).ok_or(Error::NoInsurance)?;
+
+        claimable_amount(&e, &addr, &refund, current_period)
+    }
+
+    // records that the pool was used at the current ledger, may be called by anyone. Claims made during the grace
+    // window of a period take into account the prices recorded until its end, which is known exactly when the pool
+    // was used in its last ledger and estimated from the ledger close time otherwise
+    pub fn checkpoint(e: Env) -> Result<(), Error> {
+        check_version(&e)?;
+
+        bump_instance(&e);
+
+        observe_period(&e);
+        Ok(())
+    }
+
+    // records the reference price of the current period if it wasn't yet, may be called by anyone
+    pub fn open_period(e: Env) -> Result<i128, Error> {
+        check_version(&e)?;
+
+        bump_instance(&e);
+
+        open_period(&e)
+    }
+
+    // the risk parameters `period` was priced with
+    pub fn risk_params(e: Env, period: i32) -> Result<RiskParams, Error> {
+        period_params(&e, period)
+    }
+
+    pub fn pending_risk_params(e: Env) -> Option<PendingRiskParams> {
+        get_pending_risk_params(&e)
+    }
+
+    // the policy a token was minted for
+    pub fn token_policy(e: Env, token_id: u64) -> Option<PolicyRef> {
+        read_token_policy(&e, token_id)
+    }
+
+    pub fn reference_price(e: Env, period: i32) -> Option<i128> {
+        read_reference_price(&e, period)
+    }
+
+    // last ledger of the current period at which policies can be bought
+    pub fn subscription_open_until(e: Env) -> i32 {
+        subscription_open_until(&e, actual_period(&e))
+    }
+
+    // factor (7 decimals) that a subscription made now would apply to the multiplier
+    pub fn coverage_scale(e: Env) -> Result<i128, Error> {
+        coverage_scale(&e)
+    }
+
+    // swaps the code of the pool, `migrate()` must then be called if the new code expects a newer storage version.
+    // governed pools are only upgraded through proposals
+    pub fn update(env: Env, hash: BytesN<32>) -> Result<(), Error> {
+        get_admin(&env)?.require_auth();
+        check_not_governed(&env)?;
+
+        env.deployer().update_current_contract_wasm(hash.clone());
+
+        events::upgraded(&env, hash, get_version(&env));
+        Ok(())
+    }
+
+    // runs the storage migrations the code needs after an upgrade, admin only.
+    // everything but access control and pauses is refused until it's done.
+    pub fn migrate(env: Env) -> Result<u32, Error> {
+        get_admin(&env)?.require_auth();
+
+        bump_instance(&env);
+
+        migrate(&env)
+    }
+
+    // the version of the storage and the version the code expects
+    pub fn version(env: Env) -> (u32, u32) {
+        (get_version(&env), STORAGE_VERSION)
+    }
+}
+
+#[contractimpl]
+impl Initializable for Pool {
+    fn initialize(
+        env: Env,
+        admin: Address,
+        token: Address,
+        oracle: Address,
+        assets: AssetPair,
+        periods_in_days: i32,
+        volatility: i128,
+        multiplier: i32,
+        policy_nft: Option<Address>,
+    ) -> Result<(), Error> {
+        {
+            if has_token_id(&env) {
+                return Err(Error::AlreadyInitialized);
+            }
+        }
+
+        let periods_in_ledgers = periods_in_days * DAY_IN_LEDGERS as i32;
+
+        put_admin(&env, admin);
+
+        let decimals = query_decimals(
+            &env,
+            &OracleSource {
+                kind: OracleKind::Reflector,
+                address: oracle.clone(),
+            },
+        )?;
+        put_oracle_decimals(&env, oracle.clone(), decimals);
+
+        put_oracle_id(&env, oracle);
+        put_token_id(&env, token);
+        write_genesis(&env);
+        write_periods(&env, periods_in_ledgers);
+        put_volatility(&env, volatility);
+        put_multiplier(&env, multiplier);
+        put_symbol(&env, assets.symbol);
+        match assets.base {
+            TrackedAsset::Stellar(asset) => {
+                put_external(&env, false);
+                put_oracle_asset(&env, Some(asset));
+            }
+            TrackedAsset::External => {
+                put_external(&env, true);
+                put_oracle_asset(&env, None);
+            }
+        }
+        put_quote_asset(&env, assets.quote.into_reflector());
+        if let Some(policy_nft) = policy_nft {
+            init_policy_nft(&env, &policy_nft)?;
+            put_policy_nft(&env, policy_nft);
+        }
+
+        put_version(&env, STORAGE_VERSION);
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl AccessControl for Pool {
+    fn grant_role(env: Env, caller: Address, role: Role, addr: Address) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Owner)?;
+        // the owner could otherwise take back every role
+        check_not_governed(&env)?;
+
+        bump_instance(&env);
+        set_role(&env, role, addr, true)
+    }
+
+    fn revoke_role(env: Env, caller: Address, role: Role, addr: Address) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Owner)?;
+
+        bump_instance(&env);
+        set_role(&env, role, addr, false)
+    }
+
+    fn has_role(env: Env, role: Role, addr: Address) -> Result<bool, Error> {
+        has_role(&env, role, &addr)
+    }
+
+    fn propose_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Owner)?;
+
+        bump_instance(&env);
+        put_pending_admin(&env, Some(new_admin.clone()));
+
+        events::admin_proposed(&env, caller, new_admin);
+        Ok(())
+    }
+
+    fn accept_admin(env: Env) -> Result<(), Error> {
+        let new_admin = get_pending_admin(&env).ok_or(Error::NoPendingAdmin)?;
+        new_admin.require_auth();
+
+        let previous = get_admin(&env)?;
+
+        bump_instance(&env);
+        put_admin(&env, new_admin.clone());
+        put_pending_admin(&env, None);
+
+        events::admin_changed(&env, previous, new_admin);
+        Ok(())
+    }
+
+    fn admin(env: Env) -> Result<Address, Error> {
+        get_admin(&env)
+    }
+
+    fn pending_admin(env: Env) -> Option<Address> {
+        get_pending_admin(&env)
+    }
+}
+
+#[contractimpl]
+impl Governance for Pool {
+    fn set_governance(
+        env: Env,
+        caller: Address,
+        config: Option<GovernanceConfig>,
+    ) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Owner)?;
+        check_not_governed(&env)?;
+        check_version(&env)?;
+
+        if let Some(config) = &config {
+            check_governance(config)?;
+        }
+
+        bump_instance(&env);
+        put_governance(&env, config);
+
+        events::config_updated(&env, symbol_short!("gov"));
+        Ok(())
+    }
+
+    fn propose(env: Env, proposer: Address, action: ProposalAction) -> Result<u32, Error> {
+        proposer.require_auth();
+
+        bump_instance(&env);
+
+        create_proposal(&env, proposer, action)
+    }
+
+    fn vote(env: Env, voter: Address, proposal_id: u32, support: bool) -> Result<(), Error> {
+        voter.require_auth();
+
+        bump_instance(&env);
+
+        cast_vote(&env, voter, proposal_id, support)
+    }
+
+    fn execute(env: Env, proposal_id: u32) -> Result<(), Error> {
+        bump_instance(&env);
+
+        execute_proposal(&env, proposal_id)
+    }
+
+    fn governance(env: Env) -> Option<GovernanceConfig> {
+        get_governance(&env)
+    }
+
+    fn proposal(env: Env, proposal_id: u32) -> Option<Proposal> {
+        read_proposal(&env, proposal_id)
+    }
+
+    fn has_voted(env: Env, proposal_id: u32, voter: Address) -> bool {
+        has_voted(&env, proposal_id, voter)
+    }
+}
+
+#[contractimpl]
+impl Pausable for Pool {
+    fn pause(env: Env, caller: Address, scopes: Vec<PauseScope>) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Pauser)?;
+
+        bump_instance(&env);
+
+        pause_scopes(&env, scopes);
+        Ok(())
+    }
+
+    fn unpause(env: Env, caller: Address, scope: PauseScope) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Pauser)?;
+
+        bump_instance(&env);
+
+        unpause_scope(&env, scope)
+    }
+
+    fn set_unpause_delay(env: Env, caller: Address, ledgers: u32) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Owner)?;
+
+        bump_instance(&env);
+        put_unpause_delay(&env, ledgers);
+
+        events::config_updated(&env, symbol_short!("unp_delay"));
+        Ok(())
+    }
+
+    fn paused(env: Env, scope: PauseScope) -> bool {
+        is_paused(&env, scope)
+    }
+
+    fn unpause_at(env: Env, scope: PauseScope) -> Option<u32> {
+        get_unpause_at(&env, scope)
+    }
+}
+
+#[contractimpl]
+impl Configurable for Pool {
+    fn set_pricing_model(env: Env, caller: Address, model: PricingModel) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        if let PricingModel::RealizedVolatility(config) = &model {
+            if config.records < 2
+                || config.target <= 0
+                || config.min_scale <= 0
+                || config.min_scale > config.max_scale
+            {
+                return Err(Error::InvalidConfig);
+            }
+        }
+
+        bump_instance(&env);
+        put_pricing_model(&env, model);
+
+        events::config_updated(&env, symbol_short!("pricing"));
+        Ok(())
+    }
+
+    fn set_keeper_bounty(env: Env, caller: Address, bounty: i128) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Treasury)?;
+        check_version(&env)?;
+
+        if !(0..=MAX_KEEPER_BOUNTY).contains(&bounty) {
+            return Err(Error::InvalidConfig);
+        }
+
+        bump_instance(&env);
+        put_keeper_bounty(&env, bounty);
+
+        events::config_updated(&env, symbol_short!("bounty"));
+        Ok(())
+    }
+
+    fn set_claim_grace(env: Env, caller: Address, ledgers: u32) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        // the grace window can't overlap with the grace window of the next period
+        if ledgers as i32 >= get_periods(&env) {
+            return Err(Error::InvalidConfig);
+        }
+
+        bump_instance(&env);
+        put_claim_grace(&env, ledgers);
+
+        events::config_updated(&env, symbol_short!("grace"));
+        Ok(())
+    }
+
+    fn set_trigger_mode(env: Env, caller: Address, mode: TriggerMode) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        let mut params = scheduled_params(&env)?;
+        params.trigger = mode;
+
+        bump_instance(&env);
+        propose_params(&env, params)?;
+
+        events::config_updated(&env, symbol_short!("trigger"));
+        Ok(())
+    }
+
+    fn set_oracle_guards(env: Env, caller: Address, guards: OracleGuards) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        let mut params = scheduled_params(&env)?;
+        params.guards = guards;
+
+        bump_instance(&env);
+        propose_params(&env, params)?;
+
+        events::config_updated(&env, symbol_short!("guards"));
+        Ok(())
+    }
+
+    fn set_oracles(
+        env: Env,
+        caller: Address,
+        oracles: Vec<OracleSource>,
+        quorum: OracleQuorum,
+    ) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        let mut params = scheduled_params(&env)?;
+        params.oracles = oracles;
+        params.quorum = quorum;
+
+        bump_instance(&env);
+        propose_params(&env, params)?;
+
+        events::config_updated(&env, symbol_short!("oracles"));
+        Ok(())
+    }
+
+    fn set_payout_mode(env: Env, caller: Address, mode: PayoutMode) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        let mut params = scheduled_params(&env)?;
+        params.payout = mode;
+
+        bump_instance(&env);
+        propose_params(&env, params)?;
 
-    /// withdraw_matured
+        events::config_updated(&env, symbol_short!("payout"));
+        Ok(())
+    }
 
-    /// Allows a certain user `addr` to withdraw the matured fees.
-    /// Before calling `withdraw_matured()` the user should call `update_fee_rewards`.
-    /// If not, the matured fees that were not updated will not be lost, just not included in the payment.
+    fn set_policy_terms(env: Env, caller: Address, terms: PolicyTerms) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
 
-    /// `withdraw_matured()` must be provided with:
-    /// `addr: Address` The address that is withdrawing its fee rewards.
-    fn withdraw_matured(e: Env, addr: Address, period: i32) -> Result<(), Error>;
+        check_terms(&terms)?;
 
-    /// withdraw
+        bump_instance(&env);
+        put_policy_terms(&env, terms);
 
-    /// Allows to withdraw liquidity from the pool by burning liquidity provider shares.
+        events::config_updated(&env, symbol_short!("terms"));
+        Ok(())
+    }
 
-    /// withdraw() must be provided with:
-    /// `addr: Address` Address of the liquidity provider
-    /// `amount: i28` Amount of shares that are being withdrawn
-    fn withdraw(env: Env, addr: Address, period: i32) -> Result<(), Error>;
+    fn set_coverage_caps(env: Env, caller: Address, caps: CoverageCaps) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
 
-    /// Returns the amount of shares that an address holds.
-    fn shares(e: Env, addr: Address, period: i32) -> i128;
+        if caps.max_per_policy < 0
+            || caps.max_per_address < 0
+            || !(0..=STROOP as i128).contains(&caps.max_pool_share)
+        {
+            return Err(Error::InvalidConfig);
+        }
 
-    /// Returns the amount of matured fees for an address.
-    fn matured(env: Env, addr: Address, period: i32) -> i128;
+        bump_instance(&env);
+        put_coverage_caps(&env, caps);
 
-    fn withdrawable_amount(env: Env, addr: Address, period: i32) -> i128;
-}
+        events::config_updated(&env, symbol_short!("caps"));
+        Ok(())
+    }
 
-pub trait Initializable {
-    /// initialize
+    fn set_pro_rata(env: Env, caller: Address, enabled: bool) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
 
-    /// Constructor function, only to be callable once
+        bump_instance(&env);
+        put_pro_rata(&env, enabled);
 
-    /// `initialize()` must be provided with:
-    /// `token_id: Address` The token used to manage liquidity in the pool (to subsribe insurances, pay premiums etc), and can be different from the asset pair tracked
-    /// 'volatility': the amount the asset has to move (+/-) with respect to the base to pay out premiums
-    /// 'multiplier': insurance premiums are calculated with the following formula: premium = amount + (amount * multiplier / time_until_end_of_period); the greater the multiplier, the higher the premium
-    fn initialize(
+        events::config_updated(&env, symbol_short!("prorata"));
+        Ok(())
+    }
+
+    fn set_subscription_cutoff(
         env: Env,
-        admin: Address,
-        token: Address,
-        oracle: Address,
-        symbol: Symbol,
-        external_asset: bool,
-        oracle_asset: Option<Address>, // if the asset is external, set this to None or whatever value (it will not be taken into account)
-        periods_in_days: i32,
-        volatility: i128,
-        multiplier: i32,
-    ) -> Result<(), Error>;
-}
+        caller: Address,
+        cutoff: SubscriptionCutoff,
+    ) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        // subscriptions can't be closed for the whole period
+        let valid = match &cutoff {
+            SubscriptionCutoff::None => true,
+            SubscriptionCutoff::Ledgers(ledgers) => (*ledgers as i32) < get_periods(&env),
+            SubscriptionCutoff::Fraction(fraction) => (0..STROOP as i128).contains(fraction),
+        };
+        if !valid {
+            return Err(Error::InvalidConfig);
+        }
 
-#[contractimpl]
-impl Pool {
-    pub fn glob(e: Env) -> (i128, i128, i128, i128) {
-        (
-            read_refund_global(&e, actual_period(&e)),
-            get_tot_liquidity(&e, actual_period(&e)),
-            get_fee_per_share_universal(&e, actual_period(&e)),
-            get_tot_supply(&e, actual_period(&e)), // number of liquidity provider shares
-        )
-    }
+        bump_instance(&env);
+        put_subscription_cutoff(&env, cutoff);
 
-    pub fn particular(e: Env, user: Address) -> (i128, i128, i128, Option<Insurance>) {
-        (
-            read_balance(&e, user.clone(), actual_period(&e)), // lp shares for a user
-            read_fee_per_share_particular(&e, user.clone(), actual_period(&e)),
-            read_matured_fees_particular(&e, user.clone(), actual_period(&e)),
-            read_refund_particular(&e, user.clone(), actual_period(&e)),
-        )
+        events::config_updated(&env, symbol_short!("cutoff"));
+        Ok(())
     }
 
-    pub fn get_price(e: Env) -> Option<i128> {
-        let oracle_id = get_oracle_id(&e).ok()?;
-        let client = reflector::Client::new(&e, &oracle_id);
-
-        let symbol = get_symbol(&e);
-        let external = get_external(&e);
-        let oracle_asset;
-
-        /*
-        if let Some(asset) = get_oracle_asset(&e) {
-            oracle_asset = Some(asset)
-        } else {
-            oracle_asset = None
-        }
-        */
-
-        let last_price = if external {
-            client.lastprice(&reflector::Asset::Other(symbol))
-        } else {
-            if let Some(asset) = get_oracle_asset(&e) {
-                oracle_asset = asset;
-                client.lastprice(&reflector::Asset::Stellar(oracle_asset))
-            } else {
-                None
-            }
-        };
+    fn set_strike_mode(env: Env, caller: Address, mode: StrikeMode) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
 
-        if let Some(last_price) = last_price {
-            Some(last_price.price)
-        } else {
-            None
+        if let StrikeMode::Period(max_pre_move) = &mode {
+            if !(0..=STROOP as i128).contains(max_pre_move) {
+                return Err(Error::InvalidConfig);
+            }
         }
-    }
 
-    pub fn fpsu(e: Env) -> i128 {
-        get_fee_per_share_universal(&e, actual_period(&e))
-    }
+        bump_instance(&env);
+        put_strike_mode(&env, mode);
 
-    pub fn fpsp(e: Env, user: Address) -> i128 {
-        read_fee_per_share_particular(&e, user, actual_period(&e))
+        events::config_updated(&env, symbol_short!("strike"));
+        Ok(())
     }
 
-    pub fn read_current_period(e: Env) -> i32 {
-        actual_period(&e)
-    }
+    fn set_referral_commission(env: Env, caller: Address, commission: i128) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Treasury)?;
+        check_version(&env)?;
+
+        if !(0..=MAX_REFERRAL_COMMISSION).contains(&commission) {
+            return Err(Error::InvalidConfig);
+        }
 
-    pub fn update(env: Env, hash: BytesN<32>) {
-        env.storage()
-            .instance()
-            .get::<InstanceDataKey, Address>(&InstanceDataKey::Admin)
-            .unwrap()
-            .require_auth();
+        bump_instance(&env);
+        put_referral_commission(&env, commission);
 
-        env.deployer().update_current_contract_wasm(hash);
+        events::config_updated(&env, symbol_short!("referral"));
+        Ok(())
     }
-}
 
-#[contractimpl]
-impl Initializable for Pool {
-    fn initialize(
+    fn set_premium_tokens(
         env: Env,
-        admin: Address,
-        token: Address,
-        oracle: Address,
-        symbol: Symbol,
-        external_asset: bool,
-        oracle_asset: Option<Address>,
-        periods_in_days: i32,
-        volatility: i128,
-        multiplier: i32,
+        caller: Address,
+        token_asset: QuoteAsset,
+        tokens: Vec<PremiumToken>,
     ) -> Result<(), Error> {
-        {
-            if has_token_id(&env) {
-                return Err(Error::AlreadyInitialized);
+        require_role(&env, &caller, Role::Treasury)?;
+        check_version(&env)?;
+
+        let pool_token = get_token_id(&env)?;
+        for config in tokens.iter() {
+            if config.token == pool_token || !(0..=MAX_PREMIUM_SPREAD).contains(&config.spread) {
+                return Err(Error::InvalidConfig);
             }
         }
 
-        let periods_in_ledgers = periods_in_days * DAY_IN_LEDGERS as i32;
+        bump_instance(&env);
+        put_token_asset(&env, token_asset);
+        put_premium_tokens(&env, tokens);
+
+        events::config_updated(&env, symbol_short!("premiums"));
+        Ok(())
+    }
 
-        env.storage()
-            .instance()
-            .set(&InstanceDataKey::Admin, &admin);
+    fn propose_risk_params(env: Env, caller: Address, params: RiskParams) -> Result<i32, Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
 
-        put_oracle_id(&env, oracle);
-        put_token_id(&env, token);
-        write_genesis(&env);
-        write_periods(&env, periods_in_ledgers);
-        put_volatility(&env, volatility);
-        put_multiplier(&env, multiplier);
-        put_symbol(&env, symbol);
-        put_external(&env, external_asset);
-        put_oracle_asset(&env, oracle_asset);
+        bump_instance(&env);
+
+        propose_params(&env, params)
+    }
+
+    fn cancel_risk_params(env: Env, caller: Address) -> Result<(), Error> {
+        require_role(&env, &caller, Role::RiskManager)?;
+        check_version(&env)?;
+
+        bump_instance(&env);
+        put_pending_risk_params(&env, None);
+
+        events::config_updated(&env, symbol_short!("risk_canc"));
+        Ok(())
+    }
+
+    fn set_risk_delay(env: Env, caller: Address, ledgers: u32) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Owner)?;
+        check_version(&env)?;
+
+        bump_instance(&env);
+        put_risk_delay(&env, ledgers);
+
+        events::config_updated(&env, symbol_short!("rsk_delay"));
         Ok(())
     }
 }
@@ -207,6 +1116,8 @@ impl Initializable for Pool {
 impl Vault for Pool {
     fn deposit(env: Env, from: Address, amount: i128) -> Result<(), Error> {
         check_amount_gt_0(amount)?;
+        check_not_paused(&env, PauseScope::Deposits)?;
+        check_version(&env)?;
 
         // finds current period
         let period = actual_period(&env);
@@ -214,6 +1125,7 @@ impl Vault for Pool {
         from.require_auth();
 
         bump_instance(&env);
+        observe_period(&env);
 
         // we update the rewards for the current period before the deposit to avoid the abuse of the collected fees by withdrawing them with liquidity that didn't contribute to their generation.
         update_rewards(&env, from.clone(), period);
@@ -235,6 +1147,9 @@ impl Vault for Pool {
     }
 
     fn withdraw_matured(env: Env, addr: Address, period: i32) -> Result<(), Error> {
+        check_not_paused(&env, PauseScope::Withdrawals)?;
+        check_version(&env)?;
+
         // require lender auth for withdrawal
         addr.require_auth();
 
@@ -248,6 +1163,8 @@ impl Vault for Pool {
     }
 
     fn update_fee_rewards(env: Env, addr: Address, period: i32) -> Result<(), Error> {
+        check_version(&env)?;
+
         bump_instance(&env);
 
         update_rewards(&env, addr, period);
@@ -260,12 +1177,27 @@ impl Vault for Pool {
     // 2) it's separated from the rewards
     // 3) can be less than what you deposited if the pool had some losses
     fn withdraw(env: Env, addr: Address, period: i32) -> Result<(), Error> {
+        // closed periods can be withdrawn whatever else is paused
+        check_not_paused(&env, PauseScope::Withdrawals)?;
+        check_version(&env)?;
+
         // enforce that you can't withdraw the principal for the current period
         let current_period = actual_period(&env);
         if period >= current_period {
             return Err(Error::CannotWithdraw);
         }
 
+        // nor while its policies can still be claimed
+        if env.ledger().sequence() as i32 <= period_end(&env, period) + get_claim_grace(&env) as i32
+        {
+            return Err(Error::CannotWithdraw);
+        }
+
+        // nor before the premiums it collected in other tokens are converted and credited to its shares
+        if has_foreign_premiums(&env, period) {
+            return Err(Error::CannotWithdraw);
+        }
+
         let addr_balance = read_balance(&env, addr.clone(), period);
         // if the amount is 0 return an error
         if addr_balance == 0 {
@@ -276,6 +1208,7 @@ impl Vault for Pool {
         addr.require_auth();
 
         bump_instance(&env);
+        observe_period(&env);
 
         // update addr's rewards
         update_rewards(&env, addr.clone(), period);
@@ -309,152 +1242,255 @@ impl Vault for Pool {
 }
 
 #[contractimpl]
-impl SubscribeInsurance for Pool {
-    fn subscribe(e: Env, initiator: Address, amount: i128) -> Result<(), Error> {
-        let current_period: i32 = actual_period(&e);
+impl Referral for Pool {
+    fn set_referrer(
+        env: Env,
+        caller: Address,
+        referrer: Address,
+        registered: bool,
+    ) -> Result<(), Error> {
+        require_role(&env, &caller, Role::Treasury)?;
+        check_version(&env)?;
 
-        // check subscriber doesn't have an insurance yet for the current period
-        if has_refund_particular(&e, initiator.clone(), current_period) {
-            return Err(Error::AlreadySubscribed);
-        }
+        bump_instance(&env);
+        write_referrer(&env, referrer.clone(), registered);
 
-        initiator.require_auth();
+        events::referrer_set(&env, referrer, registered);
+        Ok(())
+    }
 
-        let multiplier = get_multiplier(&e);
+    fn is_referrer(env: Env, addr: Address) -> bool {
+        is_referrer(&env, addr)
+    }
 
-        let tot_liquidity = get_tot_liquidity(&e, current_period);
-        let refund_global = read_refund_global(&e, current_period);
+    fn referral_earnings(env: Env, addr: Address) -> i128 {
+        read_referral_earnings(&env, addr)
+    }
 
-        // the time until the end of the period
-        let time_to_end = find_x(&e, current_period);
+    fn withdraw_referral_earnings(env: Env, addr: Address) -> Result<(), Error> {
+        check_version(&env)?;
 
-        // this is the refund that the policy holder would receive if the triggering condition verifies
-        // - calculated as y = amount * (1 + (1 / (q * time_to_end)))
-        // - the greater q, the greater the differential in refund prize if enter later
-        let possible_amount_to_refund =
-            calculate_refund(time_to_end as i128, amount, multiplier as i128);
+        addr.require_auth();
 
-        if refund_global + possible_amount_to_refund > tot_liquidity {
-            return Err(Error::NotEnoughLiquidity);
+        let earnings = read_referral_earnings(&env, addr.clone());
+        if earnings == 0 {
+            return Err(Error::NoEarnings);
         }
 
-        transfer_in_pool(&e, &get_token_client(&e), &initiator, &amount);
-
-        update_fee_per_share_universal(&e, amount, current_period);
-
-        let symbol = get_symbol(&e);
-        let external = get_external(&e);
-        let oracle_asset;
-
-        let reflector_price;
-
-        if external {
-            reflector_price = reflector::Client::new(&e, &get_oracle_id(&e)?)
-                .lastprice(&reflector::Asset::Other(symbol))
-                .ok_or(Error::NoPrice)?
-                .price;
-        } else {
-            if let Some(asset) = get_oracle_asset(&e) {
-                oracle_asset = asset;
-                reflector_price = reflector::Client::new(&e, &get_oracle_id(&e)?)
-                    .lastprice(&reflector::Asset::Stellar(oracle_asset))
-                    .ok_or(Error::NoPrice)?
-                    .price;
-            } else {
-                return Err(Error::NoPrice);
-            }
-        }
+        bump_instance(&env);
+
+        write_referral_earnings(&env, addr.clone(), 0);
+        transfer(&env, &get_token_client(&env), &addr, &earnings);
+
+        events::referral_withdrawn(&env, addr, earnings);
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl Premiums for Pool {
+    fn premium_quote(env: Env, token: Address, amount: i128) -> Result<i128, Error> {
+        premium_value(&env, &token, amount)
+    }
+
+    fn foreign_premiums(env: Env, token: Address, period: i32) -> i128 {
+        read_foreign_premiums(&env, token, period)
+    }
+
+    fn convert_premiums(
+        env: Env,
+        buyer: Address,
+        token: Address,
+        period: i32,
+        amount: i128,
+    ) -> Result<(), Error> {
+        check_amount_gt_0(amount)?;
+        check_version(&env)?;
+
+        buyer.require_auth();
+
+        bump_instance(&env);
+
+        convert_premiums(&env, buyer, token, period, amount)?;
+        Ok(())
+    }
+}
+
+#[contractimpl]
+impl Reserve for Pool {
+    fn fund_reserve(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+        check_amount_gt_0(amount)?;
+        check_version(&env)?;
+
+        from.require_auth();
+
+        bump_instance(&env);
+
+        transfer_in_pool(&env, &get_token_client(&env), &from, &amount);
+        put_reserve_fund(&env, get_reserve_fund(&env) + amount);
+
+        events::reserve_funded(&env, from, amount);
+        Ok(())
+    }
+
+    fn claim_shortfall(env: Env, claimant: Address, period: i32) -> Result<(), Error> {
+        claimant.require_auth();
+
+        bump_instance(&env);
+
+        let paid = execute_shortfall_claim(&env, claimant.clone(), period)?;
+
+        events::befenit_payout(&env, claimant, paid, period);
+        Ok(())
+    }
+
+    fn shortfall(env: Env, addr: Address, period: i32) -> i128 {
+        read_shortfall(&env, addr, period)
+    }
 
-        /*
-        // reflector contract hardcoded into the contract: to change
-        let reflector_price: i128 = reflector::Client::new(&e, &get_oracle_id(&e)?)
-            .lastprice(&reflector::Asset::Other(symbol_short!("UNI")))
-            .ok_or(Error::NoPrice)?
-            .price;
-        */
+    fn reserve_fund(env: Env) -> i128 {
+        get_reserve_fund(&env)
+    }
+}
 
-        write_refund_particular(
+#[contractimpl]
+impl SubscribeInsurance for Pool {
+    fn subscribe(e: Env, initiator: Address, amount: i128) -> Result<(), Error> {
+        subscribe_policy(
             &e,
             initiator.clone(),
-            possible_amount_to_refund,
-            reflector_price,
-            current_period,
-        );
+            initiator,
+            None,
+            amount,
+            get_policy_terms(&e),
+            None,
+        )
+    }
+
+    fn subscribe_for(
+        e: Env,
+        payer: Address,
+        beneficiary: Address,
+        amount: i128,
+        referrer: Option<Address>,
+    ) -> Result<(), Error> {
+        subscribe_policy(
+            &e,
+            payer,
+            beneficiary,
+            None,
+            amount,
+            get_policy_terms(&e),
+            referrer,
+        )
+    }
+
+    fn subscribe_batch(e: Env, payer: Address, entries: Vec<(Address, i128)>) -> Result<(), Error> {
+        subscribe_batch(&e, payer, entries)
+    }
 
-        write_refund_global(
+    fn subscribe_with_token(
+        e: Env,
+        payer: Address,
+        beneficiary: Address,
+        token: Address,
+        amount: i128,
+        referrer: Option<Address>,
+    ) -> Result<(), Error> {
+        subscribe_policy(
             &e,
-            refund_global + possible_amount_to_refund,
-            current_period,
-        );
+            payer,
+            beneficiary,
+            Some(token),
+            amount,
+            get_policy_terms(&e),
+            referrer,
+        )
+    }
 
-        // bump_instance(&e);
+    fn subscribe_with_terms(
+        e: Env,
+        initiator: Address,
+        amount: i128,
+        terms: PolicyTerms,
+    ) -> Result<(), Error> {
+        check_terms(&terms)?;
 
-        events::policy_purchase(&e, initiator, amount, current_period);
-        Ok(())
+        // buyers can take on more of the risk than the pool requires, not less
+        let pool_terms = get_policy_terms(&e);
+        if terms.deductible < pool_terms.deductible || terms.coinsurance < pool_terms.coinsurance {
+            return Err(Error::InvalidTerms);
+        }
+
+        subscribe_policy(&e, initiator.clone(), initiator, None, amount, terms, None)
     }
 
     fn claim_reward(e: Env, claimant: Address) -> Result<(), Error> {
         let current_period = actual_period(&e);
-        claimant.require_auth();
 
-        // check if you had an available possible refund in the current period
-        let refund = read_refund_particular(&e, claimant.clone(), current_period)
-            .ok_or(Error::NoInsurance)?;
-
-        let symbol = get_symbol(&e);
-        let external = get_external(&e);
-        let oracle_asset;
-
-        let reflector_price;
-
-        if external {
-            reflector_price = reflector::Client::new(&e, &get_oracle_id(&e)?)
-                .lastprice(&reflector::Asset::Other(symbol))
-                .ok_or(Error::NoPrice)?
-                .price;
-        } else {
-            if let Some(asset) = get_oracle_asset(&e) {
-                oracle_asset = asset;
-                reflector_price = reflector::Client::new(&e, &get_oracle_id(&e)?)
-                    .lastprice(&reflector::Asset::Stellar(oracle_asset))
-                    .ok_or(Error::NoPrice)?
-                    .price;
-            } else {
-                return Err(Error::NoPrice);
-            }
-        }
+        let (recipient, paid) = execute_claim(&e, claimant, current_period, None)?;
 
-        /*
-                let reflector_price = reflector::Client::new(&e, &get_oracle_id(&e)?)
-                    .lastprice(&reflector::Asset::Other(symbol_short!("UNI")))
-                    .ok_or(Error::NoPrice)?
-                    .price;
-        */
+        bump_instance(&e);
 
-        let volatility = get_volatility(&e)?;
+        events::befenit_payout(&e, recipient, paid, current_period);
+        Ok(())
+    }
 
-        if refund.price + volatility < reflector_price
-            || refund.price - volatility > reflector_price
-        {
-            transfer(&e, &get_token_client(&e), &claimant, &refund.amount);
-            let tot_liquidity = get_tot_liquidity(&e, current_period);
-            let refund_global = read_refund_global(&e, current_period);
-
-            write_refund_global(&e, refund_global - refund.amount, current_period);
-            put_tot_liquidity(&e, tot_liquidity - refund.amount, current_period);
-            e.storage()
-                .persistent()
-                .remove(&PersistentDataKey::RefundParticular(BalanceObject::new(
-                    claimant.clone(),
-                    current_period,
-                )))
-        } else {
-            return Err(Error::UnmetCondition);
+    fn settle_claim(e: Env, keeper: Address, policyholder: Address) -> Result<(), Error> {
+        let current_period = actual_period(&e);
+        keeper.require_auth();
+
+        let (recipient, paid) = execute_claim(&e, policyholder, current_period, Some(keeper))?;
+
+        bump_instance(&e);
+
+        events::befenit_payout(&e, recipient, paid, current_period);
+        Ok(())
+    }
+
+    fn claim_previous(e: Env, claimant: Address) -> Result<(), Error> {
+        let period = actual_period(&e) - 1;
+
+        let (recipient, paid) = execute_claim(&e, claimant, period, None)?;
+
+        bump_instance(&e);
+
+        events::befenit_payout(&e, recipient, paid, period);
+        Ok(())
+    }
+
+    fn settle_previous(e: Env, keeper: Address, policyholder: Address) -> Result<(), Error> {
+        let period = actual_period(&e) - 1;
+        keeper.require_auth();
+
+        let (recipient, paid) = execute_claim(&e, policyholder, period, Some(keeper))?;
+
+        bump_instance(&e);
+
+        events::befenit_payout(&e, recipient, paid, period);
+        Ok(())
+    }
+
+    fn claim_policy(e: Env, claimant: Address, token_id: u64) -> Result<(), Error> {
+        let policy = read_token_policy(&e, token_id).ok_or(Error::NoInsurance)?;
+        if policy_owner(&e, policy.beneficiary.clone(), Some(token_id)) != claimant {
+            return Err(Error::NotTokenOwner);
         }
 
+        // the claim is authorized by the owner of the token
+        let (recipient, paid) = execute_claim(&e, policy.beneficiary, policy.period, None)?;
+
         bump_instance(&e);
 
-        events::befenit_payout(&e, claimant, refund.amount, current_period);
+        events::befenit_payout(&e, recipient, paid, policy.period);
         Ok(())
     }
+
+    fn record_prices(e: Env, policyholder: Address, period: i32) -> Result<u64, Error> {
+        let until = record_prices(&e, policyholder, period)?;
+
+        bump_instance(&e);
+
+        Ok(until)
+    }
 }
@@ -1,4 +1,5 @@
-use soroban_sdk::{symbol_short, Address, Env};
+use crate::types::{PauseScope, ProposalAction, RiskParams, Role};
+use soroban_sdk::{symbol_short, Address, BytesN, Env, Symbol};
 
 pub(crate) fn deposited(env: &Env, from: Address, amount: i128, period: i32) {
     let topics = (symbol_short!("deposit"), from, period);
@@ -25,7 +26,144 @@ pub(crate) fn policy_purchase(env: &Env, from: Address, premium: i128, period: i
     env.events().publish(topics, premium);
 }
 
+pub(crate) fn policy_sponsored(env: &Env, payer: Address, beneficiary: Address, period: i32) {
+    let topics = (symbol_short!("sponsor"), payer, period);
+    env.events().publish(topics, beneficiary);
+}
+
+pub(crate) fn referral(env: &Env, referrer: Address, commission: i128, period: i32) {
+    let topics = (symbol_short!("referral"), referrer, period);
+    env.events().publish(topics, commission);
+}
+
+pub(crate) fn referrer_set(env: &Env, referrer: Address, registered: bool) {
+    let topics = (symbol_short!("referrer"), referrer);
+    env.events().publish(topics, registered);
+}
+
+pub(crate) fn foreign_referral(
+    env: &Env,
+    referrer: Address,
+    token: Address,
+    commission: i128,
+    period: i32,
+) {
+    let topics = (symbol_short!("refpaid"), referrer, token, period);
+    env.events().publish(topics, commission);
+}
+
+pub(crate) fn referral_withdrawn(env: &Env, referrer: Address, amount: i128) {
+    let topics = (symbol_short!("refwithdr"), referrer);
+    env.events().publish(topics, amount);
+}
+
+pub(crate) fn premiums_converted(
+    env: &Env,
+    buyer: Address,
+    token: Address,
+    amount: i128,
+    price: i128,
+) {
+    let topics = (symbol_short!("convert"), buyer, token);
+    env.events().publish(topics, (amount, price));
+}
+
 pub(crate) fn befenit_payout(env: &Env, from: Address, benefit: i128, period: i32) {
     let topics = (symbol_short!("payout"), from, period);
     env.events().publish(topics, benefit);
-}
\ No newline at end of file
+}
+
+pub(crate) fn shortfall(env: &Env, holder: Address, unpaid: i128, period: i32) {
+    let topics = (symbol_short!("shortfall"), holder, period);
+    env.events().publish(topics, unpaid);
+}
+
+pub(crate) fn reserve_funded(env: &Env, from: Address, amount: i128) {
+    let topics = (symbol_short!("reserve"), from);
+    env.events().publish(topics, amount);
+}
+
+pub(crate) fn keeper_bounty(env: &Env, keeper: Address, bounty: i128, period: i32) {
+    let topics = (symbol_short!("bounty"), keeper, period);
+    env.events().publish(topics, bounty);
+}
+
+pub(crate) fn period_opened(env: &Env, reference_price: i128, period: i32) {
+    let topics = (symbol_short!("opened"), period);
+    env.events().publish(topics, reference_price);
+}
+
+pub(crate) fn role_updated(env: &Env, role: Role, addr: Address, granted: bool) {
+    let topics = (symbol_short!("role"), role, addr);
+    env.events().publish(topics, granted);
+}
+
+pub(crate) fn admin_proposed(env: &Env, admin: Address, proposed: Address) {
+    let topics = (symbol_short!("adm_prop"), admin);
+    env.events().publish(topics, proposed);
+}
+
+pub(crate) fn admin_changed(env: &Env, previous: Address, admin: Address) {
+    let topics = (symbol_short!("adm_chg"), previous);
+    env.events().publish(topics, admin);
+}
+
+pub(crate) fn paused(env: &Env, scope: PauseScope) {
+    let topics = (symbol_short!("paused"), scope);
+    env.events().publish(topics, ());
+}
+
+pub(crate) fn unpause_scheduled(env: &Env, scope: PauseScope, ledger: u32) {
+    let topics = (symbol_short!("unp_sched"), scope);
+    env.events().publish(topics, ledger);
+}
+
+pub(crate) fn unpaused(env: &Env, scope: PauseScope) {
+    let topics = (symbol_short!("unpaused"), scope);
+    env.events().publish(topics, ());
+}
+
+pub(crate) fn upgrade_scheduled(env: &Env, hash: BytesN<32>, unlocks: u32) {
+    let topics = (symbol_short!("upg_sched"), unlocks);
+    env.events().publish(topics, hash);
+}
+
+pub(crate) fn upgraded(env: &Env, hash: BytesN<32>, version: u32) {
+    let topics = (symbol_short!("upgraded"), version);
+    env.events().publish(topics, hash);
+}
+
+pub(crate) fn migrated(env: &Env, from: u32, to: u32) {
+    let topics = (symbol_short!("migrated"), from);
+    env.events().publish(topics, to);
+}
+
+pub(crate) fn proposal_created(env: &Env, id: u32, proposer: Address, action: ProposalAction) {
+    let topics = (symbol_short!("proposed"), id, proposer);
+    env.events().publish(topics, action);
+}
+
+pub(crate) fn voted(env: &Env, id: u32, voter: Address, support: bool, weight: i128) {
+    let topics = (symbol_short!("voted"), id, voter);
+    env.events().publish(topics, (support, weight));
+}
+
+pub(crate) fn proposal_executed(env: &Env, id: u32) {
+    let topics = (symbol_short!("executed"), id);
+    env.events().publish(topics, ());
+}
+
+pub(crate) fn risk_params_proposed(env: &Env, params: RiskParams, effective_period: i32) {
+    let topics = (symbol_short!("risk_prop"), effective_period);
+    env.events().publish(topics, params);
+}
+
+pub(crate) fn risk_params_activated(env: &Env, params: RiskParams, period: i32) {
+    let topics = (symbol_short!("risk_act"), period);
+    env.events().publish(topics, params);
+}
+
+pub(crate) fn config_updated(env: &Env, setting: Symbol) {
+    let topics = (symbol_short!("config"), setting);
+    env.events().publish(topics, ());
+}
@@ -0,0 +1,207 @@
+use crate::{
+    access::set_role,
+    checks::{check_governance, check_terms, check_version},
+    events,
+    math::actual_period,
+    pause::{pause_scopes, unpause_scope},
+    risk::propose_params,
+    storage::*,
+    types::{Error, GovernanceConfig, Proposal, ProposalAction},
+};
+use fixed_point_math::{FixedPoint, STROOP};
+use soroban_sdk::{symbol_short, Address, Env};
+
+/// Opens a vote on `action`. Only liquidity providers of the current period can propose, and only they can vote.
+/// Returns the id of the proposal.
+pub(crate) fn create_proposal(
+    e: &Env,
+    proposer: Address,
+    action: ProposalAction,
+) -> Result<u32, Error> {
+    check_version(e)?;
+
+    let config = get_governance(e).ok_or(Error::GovernanceDisabled)?;
+
+    let period = actual_period(e);
+    let created = e.ledger().sequence();
+
+    if read_balance(e, proposer.clone(), period) == 0 {
+        return Err(Error::NoVotingPower);
+    }
+
+    let voting_ends = created + config.voting_period;
+
+    let id = next_proposal_id(e);
+    write_proposal(
+        e,
+        id,
+        &Proposal {
+            proposer: proposer.clone(),
+            action: action.clone(),
+            period,
+            created,
+            total_weight: get_tot_supply(e, period),
+            voting_ends,
+            // approved proposals can be executed for as long as they were voted on
+            expires: voting_ends + config.voting_period,
+            unlocks: 0,
+            votes_for: 0,
+            votes_against: 0,
+            executed: false,
+        },
+    );
+
+    events::proposal_created(e, id, proposer, action);
+    Ok(id)
+}
+
+/// Votes on proposal `id` with `voter`'s LP shares in the proposal's period, as they were when it was created.
+pub(crate) fn cast_vote(e: &Env, voter: Address, id: u32, support: bool) -> Result<(), Error> {
+    check_version(e)?;
+
+    let mut proposal = read_proposal(e, id).ok_or(Error::NoProposal)?;
+
+    if e.ledger().sequence() > proposal.voting_ends {
+        return Err(Error::VotingClosed);
+    }
+
+    if has_voted(e, id, voter.clone()) {
+        return Err(Error::AlreadyVoted);
+    }
+
+    // shares can't be withdrawn before their period ends, so they can't be moved to vote twice
+    let weight = voting_weight(e, &voter, &proposal);
+    if weight == 0 {
+        return Err(Error::NoVotingPower);
+    }
+
+    if support {
+        proposal.votes_for += weight;
+    } else {
+        proposal.votes_against += weight;
+    }
+
+    write_vote(e, id, voter.clone(), weight);
+    write_proposal(e, id, &proposal);
+
+    events::voted(e, id, voter, support, weight);
+    Ok(())
+}
+
+/// The shares `voter` held in the period of `proposal` before it was created. Deposits made since then don't count.
+fn voting_weight(e: &Env, voter: &Address, proposal: &Proposal) -> i128 {
+    for (ledger, balance) in read_deposit_history(e, voter.clone(), proposal.period).iter() {
+        if ledger >= proposal.created {
+            return balance;
+        }
+    }
+
+    read_balance(e, voter.clone(), proposal.period)
+}
+
+/// Executes proposal `id` once its vote ended, if it reached the quorum and a majority voted for it.
+/// Upgrades and unpauses, when the pool has an unpause delay, are timelocked: the first execution schedules them and
+/// the proposal has to be executed again once the delay elapsed, which fails with `Timelocked` before that. Scheduling
+/// gives the proposal another voting period from the end of the delay to be completed.
+pub(crate) fn execute_proposal(e: &Env, id: u32) -> Result<(), Error> {
+    check_version(e)?;
+
+    let config = get_governance(e).ok_or(Error::GovernanceDisabled)?;
+    let mut proposal = read_proposal(e, id).ok_or(Error::NoProposal)?;
+
+    if proposal.executed {
+        return Err(Error::AlreadyExecuted);
+    }
+
+    let ledger = e.ledger().sequence();
+    if ledger <= proposal.voting_ends {
+        return Err(Error::VotingOpen);
+    }
+
+    check_approved(&config, &proposal, ledger)?;
+
+    match proposal.action.clone() {
+        ProposalAction::RiskParams(params) => {
+            propose_params(e, params)?;
+        }
+        ProposalAction::PolicyTerms(terms) => {
+            check_terms(&terms)?;
+            put_policy_terms(e, terms);
+            events::config_updated(e, symbol_short!("terms"));
+        }
+        ProposalAction::Pause(scopes) => pause_scopes(e, scopes),
+        ProposalAction::Unpause(scope) => {
+            unpause_scope(e, scope)?;
+
+            if let Some(unpause_at) = get_unpause_at(e, scope) {
+                schedule(e, id, &mut proposal, &config, unpause_at);
+                return Ok(());
+            }
+        }
+        ProposalAction::Upgrade(hash) => {
+            if proposal.unlocks == 0 {
+                schedule(e, id, &mut proposal, &config, ledger + config.upgrade_delay);
+
+                events::upgrade_scheduled(e, hash, proposal.unlocks);
+                return Ok(());
+            }
+
+            if ledger < proposal.unlocks {
+                return Err(Error::Timelocked);
+            }
+
+            e.deployer().update_current_contract_wasm(hash.clone());
+            events::upgraded(e, hash, get_version(e));
+        }
+        ProposalAction::Governance(config) => {
+            check_governance(&config)?;
+
+            put_governance(e, Some(config));
+            events::config_updated(e, symbol_short!("gov"));
+        }
+        ProposalAction::EndGovernance => {
+            put_governance(e, None);
+            events::config_updated(e, symbol_short!("gov"));
+        }
+        ProposalAction::GrantRole(role, addr) => set_role(e, role, addr, true)?,
+        ProposalAction::RevokeRole(role, addr) => set_role(e, role, addr, false)?,
+    }
+
+    proposal.executed = true;
+    write_proposal(e, id, &proposal);
+
+    events::proposal_executed(e, id);
+    Ok(())
+}
+
+// records that the action of `proposal` can be completed from `unlocks`, leaving it a voting period to be
+fn schedule(e: &Env, id: u32, proposal: &mut Proposal, config: &GovernanceConfig, unlocks: u32) {
+    proposal.unlocks = unlocks;
+    proposal.expires = proposal.expires.max(unlocks + config.voting_period);
+    write_proposal(e, id, proposal);
+}
+
+// makes sure the vote on `proposal` passed and the proposal can still be executed at `ledger`
+fn check_approved(
+    config: &GovernanceConfig,
+    proposal: &Proposal,
+    ledger: u32,
+) -> Result<(), Error> {
+    if ledger > proposal.expires {
+        return Err(Error::ProposalExpired);
+    }
+
+    let quorum = proposal
+        .total_weight
+        .fixed_mul_ceil(config.quorum, STROOP.into())
+        .unwrap();
+    if proposal.votes_for + proposal.votes_against < quorum {
+        return Err(Error::NoQuorum);
+    }
+
+    if proposal.votes_for <= proposal.votes_against {
+        return Err(Error::ProposalRejected);
+    }
+
+    Ok(())
+}
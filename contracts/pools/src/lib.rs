@@ -7,14 +7,26 @@ mod reflector {
 
 use fixed_point_math::FixedPoint;
 
+mod access;
 mod balance;
 mod checks;
+mod claims;
 pub mod contract;
 mod events;
 mod execution;
+mod governance;
 pub mod math;
+mod migrations;
+mod oracle;
+mod pause;
+mod policy_nft;
+mod premiums;
+mod price_source;
+mod pricing;
 mod rewards;
+mod risk;
 mod storage;
+mod subscription;
 mod token_utility;
 mod types;
 
@@ -25,6 +37,35 @@ pub fn compute_fee(amount: &i128) -> i128 {
 // These numbers are conservative but considering the SACs numbers (SACs are much more likely to be invoked)
 // they seem reasonable.
 
+// keepers can't take more than 10% of a payout
+pub(crate) const MAX_KEEPER_BOUNTY: i128 = 1_000_000;
+
+// referrers can't take more than 20% of a premium
+pub(crate) const MAX_REFERRAL_COMMISSION: i128 = 2_000_000;
+
+// premiums paid in other tokens lose at most 10% in the conversion
+pub(crate) const MAX_PREMIUM_SPREAD: i128 = 1_000_000;
+
+// policyholders bear at most 90% of a payout through co-insurance
+pub(crate) const MAX_COINSURANCE: i128 = 9_000_000;
+
+// buyers taking on a deductible or co-insurance are credited half of the liability they take off the pool
+pub(crate) const TERMS_CREDIT: i128 = 5_000_000;
+
+// Reflector doesn't return more records than this for a single `prices` call
+pub(crate) const MAX_ORACLE_RECORDS: u32 = 20;
+
+pub(crate) const MAX_ORACLE_SOURCES: u32 = 5;
+
+// prices from every oracle are normalized to 7 decimals, like the other fixed point values of the pool
+pub(crate) const PRICE_DECIMALS: u32 = 7;
+
+// version of the storage layout this code reads and writes, see `migrations`
+pub(crate) const STORAGE_VERSION: u32 = 1;
+
+// seconds between two ledgers, which `DAY_IN_LEDGERS` is based on
+pub(crate) const LEDGER_CLOSE_TIME: u64 = 5;
+
 pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
 pub(crate) const INSTANCE_LEDGER_LIFE: u32 = 30 * DAY_IN_LEDGERS; // ~30 days.
 pub(crate) const INSTANCE_LEDGER_TTL_THRESHOLD: u32 = INSTANCE_LEDGER_LIFE - DAY_IN_LEDGERS;
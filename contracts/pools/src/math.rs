@@ -1,7 +1,12 @@
-use crate::storage::{get_genesis, get_periods};
+use crate::{
+    reflector::PriceData,
+    storage::{get_genesis, get_periods},
+    types::PolicyTerms,
+    PRICE_DECIMALS, TERMS_CREDIT,
+};
 use core::ops::{Add, Sub};
 use fixed_point_math::{FixedPoint, STROOP};
-use soroban_sdk::Env;
+use soroban_sdk::{Env, Vec};
 
 pub fn compute_fee_per_share(
     fee_per_share_universal: i128,
@@ -71,6 +76,11 @@ pub(crate) fn actual_period(e: &Env) -> i32 {
     )
 }
 
+// last ledger of the period (periods are closed on the right, see `calculate_period`)
+pub(crate) fn period_end(e: &Env, period: i32) -> i32 {
+    get_genesis(e) + period * get_periods(e)
+}
+
 pub(crate) fn calculate_to_mint(
     e: &Env,
     amount: i128,
@@ -110,8 +120,136 @@ pub(crate) fn find_x(env: &Env, current_period: i32) -> i32 {
     x
 }
 
-pub(crate) fn calculate_refund(time_to_end: i128, amount: i128, multiplier: i128) -> i128 {
-    let coefficient = amount * multiplier / time_to_end;
+// `scale` (7 decimals) adjusts the multiplier part of the refund, see `pricing::coverage_scale`
+pub(crate) fn calculate_refund(
+    time_to_end: i128,
+    amount: i128,
+    multiplier: i128,
+    scale: i128,
+) -> i128 {
+    let coefficient = (amount * multiplier)
+        .fixed_mul_floor(scale, STROOP.into())
+        .unwrap()
+        / time_to_end;
 
     amount + coefficient
 }
+
+// mean absolute return between consecutive records (7 decimals), records are ordered most recent first
+pub(crate) fn realized_volatility(history: &Vec<PriceData>) -> Option<i128> {
+    if history.len() < 2 {
+        return None;
+    }
+
+    let mut sum = 0;
+    for i in 1..history.len() {
+        let newer = history.get_unchecked(i - 1).price;
+        let older = history.get_unchecked(i).price;
+        if older <= 0 {
+            return None;
+        }
+
+        sum += (newer - older)
+            .abs()
+            .fixed_div_floor(older, STROOP.into())
+            .unwrap();
+    }
+
+    Some(sum / (history.len() as i128 - 1))
+}
+
+// the more volatile the asset the less coverage each unit of premium buys
+pub(crate) fn volatility_scale(
+    realized: i128,
+    target: i128,
+    min_scale: i128,
+    max_scale: i128,
+) -> i128 {
+    if realized == 0 {
+        return max_scale;
+    }
+
+    target
+        .fixed_div_floor(realized, STROOP.into())
+        .unwrap()
+        .clamp(min_scale, max_scale)
+}
+
+// relative move (7 decimals) from `from` to `to`
+pub(crate) fn relative_move(from: i128, to: i128) -> Option<i128> {
+    if from <= 0 {
+        return None;
+    }
+
+    (to - from).abs().fixed_div_ceil(from, STROOP.into())
+}
+
+// `sorted` must be sorted in ascending order and not empty
+pub(crate) fn median(sorted: &Vec<i128>) -> i128 {
+    let middle = sorted.len() / 2;
+
+    if sorted.len() % 2 == 0 {
+        (sorted.get_unchecked(middle - 1) + sorted.get_unchecked(middle)) / 2
+    } else {
+        sorted.get_unchecked(middle)
+    }
+}
+
+// converts a price reported with `decimals` decimals to `PRICE_DECIMALS`
+pub(crate) fn normalize_price(price: i128, decimals: u32) -> i128 {
+    rescale(price, decimals, PRICE_DECIMALS)
+}
+
+// converts `amount` from `from` decimals to `to` decimals, rounding down
+pub(crate) fn rescale(amount: i128, from: u32, to: u32) -> i128 {
+    if from > to {
+        amount / 10_i128.pow(from - to)
+    } else {
+        amount * 10_i128.pow(to - from)
+    }
+}
+
+// pays nothing below `attachment`, the whole `amount` from `exhaustion` and linearly in between (moves are 7 decimals)
+pub(crate) fn linear_payout(
+    amount: i128,
+    price_move: i128,
+    attachment: i128,
+    exhaustion: i128,
+) -> i128 {
+    if price_move <= attachment {
+        return 0;
+    }
+
+    if price_move >= exhaustion {
+        return amount;
+    }
+
+    amount
+        .fixed_mul_floor(price_move - attachment, exhaustion - attachment)
+        .unwrap()
+}
+
+// what a policy pays out of a `gross` payout once its deductible and co-insurance are applied
+pub(crate) fn apply_terms(gross: i128, terms: &PolicyTerms) -> i128 {
+    (gross - terms.deductible)
+        .max(0)
+        .fixed_mul_floor(STROOP as i128 - terms.coinsurance, STROOP.into())
+        .unwrap()
+}
+
+// the coverage bought under `terms` with a premium that buys `refund` without them. Only `TERMS_CREDIT` of the
+// liability taken off the pool is given back as coverage, so the most the policy pays is lower than `refund`
+pub(crate) fn gross_coverage(refund: i128, terms: &PolicyTerms) -> i128 {
+    let credited_coinsurance = terms
+        .coinsurance
+        .fixed_mul_floor(TERMS_CREDIT, STROOP.into())
+        .unwrap();
+
+    refund
+        .fixed_div_floor(STROOP as i128 - credited_coinsurance, STROOP.into())
+        .unwrap()
+        + terms
+            .deductible
+            .fixed_mul_floor(TERMS_CREDIT, STROOP.into())
+            .unwrap()
+}
@@ -0,0 +1,37 @@
+use crate::{events, storage::*, types::Error, STORAGE_VERSION};
+use soroban_sdk::Env;
+
+// brings the storage from `version` to `version + 1`
+fn migrate_from(_e: &Env, version: u32) {
+    match version {
+        // every key added since the first deployments has a default, and policies sold before the version was
+        // recorded are read in their original layout (see `LegacyInsurance`), so these pools only need to start
+        // recording it
+        0 => {}
+        _ => unreachable!(),
+    }
+}
+
+/// Runs every migration between the version in storage and `STORAGE_VERSION`, in order.
+/// Returns the version the storage was at.
+pub(crate) fn migrate(e: &Env) -> Result<u32, Error> {
+    let from = get_version(e);
+
+    if from == STORAGE_VERSION {
+        return Err(Error::UpToDate);
+    }
+
+    // code older than the storage can't know how to read it
+    if from > STORAGE_VERSION {
+        return Err(Error::VersionMismatch);
+    }
+
+    for version in from..STORAGE_VERSION {
+        migrate_from(e, version);
+    }
+
+    put_version(e, STORAGE_VERSION);
+
+    events::migrated(e, from, STORAGE_VERSION);
+    Ok(from)
+}
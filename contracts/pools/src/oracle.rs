@@ -0,0 +1,262 @@
+use crate::{
+    math::{median, normalize_price, relative_move},
+    price_source::{PriceSource, Source},
+    reflector,
+    risk::{current_params, period_params},
+    storage::{get_external, get_oracle_asset, get_quote_asset},
+    types::{AggregationMode, Error, OracleGuards, PriceInfo, QuoteAsset, RiskParams},
+    MAX_ORACLE_RECORDS,
+};
+use soroban_sdk::{Env, Vec};
+
+/// The asset followed by the pool, as understood by the Reflector oracle.
+/// For pair pools this is the base asset, priced in the quote asset.
+pub(crate) fn tracked_asset(e: &Env, params: &RiskParams) -> Result<reflector::Asset, Error> {
+    if get_external(e) {
+        Ok(reflector::Asset::Other(params.symbol.clone()))
+    } else {
+        get_oracle_asset(e)
+            .map(reflector::Asset::Stellar)
+            .ok_or(Error::NoPrice)
+    }
+}
+
+// the pool's oracle sources under `params`, in fallback order
+fn sources<'a>(e: &'a Env, params: &RiskParams) -> Result<impl Iterator<Item = Source<'a>>, Error> {
+    let asset = tracked_asset(e, params)?;
+    let symbol = params.symbol.clone();
+    let quote = get_quote_asset(e);
+
+    Ok(params
+        .oracles
+        .clone()
+        .into_iter()
+        .map(move |config| Source::new(e, &config, &asset, &symbol, &quote)))
+}
+
+/// The last price of every oracle source that answers, raw and normalized.
+pub(crate) fn source_prices(e: &Env) -> Result<Vec<PriceInfo>, Error> {
+    let params = current_params(e)?;
+    let mut infos = Vec::new(e);
+
+    for (config, source) in params.oracles.iter().zip(sources(e, &params)?) {
+        if let Some(data) = source.raw_last_price() {
+            infos.push_back(PriceInfo {
+                source: config.address,
+                raw: data.price,
+                decimals: source.decimals(),
+                normalized: normalize_price(data.price, source.decimals()),
+                timestamp: data.timestamp,
+            });
+        }
+    }
+
+    Ok(infos)
+}
+
+// the first answer in the pool's fallback ordering of the oracle sources
+fn first_answer<T>(
+    e: &Env,
+    params: &RiskParams,
+    query: impl Fn(&Source) -> Option<T>,
+) -> Result<T, Error> {
+    for source in sources(e, params)? {
+        if let Some(value) = query(&source) {
+            return Ok(value);
+        }
+    }
+
+    Err(Error::NoPrice)
+}
+
+fn check_age(e: &Env, guards: &OracleGuards, last: &reflector::PriceData) -> Result<(), Error> {
+    if guards.max_age > 0 && e.ledger().timestamp().saturating_sub(last.timestamp) > guards.max_age
+    {
+        return Err(Error::StalePrice);
+    }
+
+    Ok(())
+}
+
+/// The last price reported by a single `source`, once it passed the pool's `OracleGuards`.
+fn source_last_price(e: &Env, source: &Source, guards: &OracleGuards) -> Result<i128, Error> {
+    let last = source.last_price().ok_or(Error::NoPrice)?;
+    check_age(e, guards, &last)?;
+
+    let records = if guards.max_deviation > 0 {
+        guards.min_records.max(2)
+    } else {
+        guards.min_records
+    };
+
+    if records > 0 {
+        let history = source.prices(records).ok_or(Error::StalePrice)?;
+        if history.len() < guards.min_records {
+            return Err(Error::StalePrice);
+        }
+
+        if guards.max_deviation > 0 && history.len() >= 2 {
+            let deviation = relative_move(
+                history.get_unchecked(1).price,
+                history.get_unchecked(0).price,
+            )
+            .ok_or(Error::PriceDeviation)?;
+            if deviation > guards.max_deviation {
+                return Err(Error::PriceDeviation);
+            }
+        }
+    }
+
+    Ok(last.price)
+}
+
+/// The last price of the tracked asset, aggregated over the pool's oracle sources according to its `OracleQuorum`.
+pub(crate) fn last_price(e: &Env) -> Result<i128, Error> {
+    let params = current_params(e)?;
+    quorum_price(e, &params, sources(e, &params)?)
+}
+
+// the last price of `sources` that passed the guards, combined according to the pool's `OracleQuorum`
+fn quorum_price<'a>(
+    e: &Env,
+    params: &RiskParams,
+    sources: impl Iterator<Item = Source<'a>>,
+) -> Result<i128, Error> {
+    let quorum = &params.quorum;
+
+    let mut prices: Vec<i128> = Vec::new(e);
+    let mut error = Error::NoPrice;
+
+    for source in sources {
+        match source_last_price(e, &source, &params.guards) {
+            Ok(price) => {
+                if quorum.mode == AggregationMode::Fallback {
+                    return Ok(price);
+                }
+
+                // keep the prices sorted to take the median
+                let position = prices
+                    .iter()
+                    .position(|p| p > price)
+                    .unwrap_or(prices.len() as usize);
+                prices.insert(position as u32, price);
+            }
+            Err(source_error) => error = source_error,
+        }
+    }
+
+    if prices.is_empty() {
+        return Err(error);
+    }
+
+    if prices.len() < quorum.min_sources {
+        return Err(Error::NoQuorum);
+    }
+
+    let median = median(&prices);
+
+    if quorum.max_divergence > 0 {
+        let lowest = relative_move(median, prices.first_unchecked()).ok_or(Error::NoPrice)?;
+        let highest = relative_move(median, prices.last_unchecked()).ok_or(Error::NoPrice)?;
+        if lowest.max(highest) > quorum.max_divergence {
+            return Err(Error::OracleDivergence);
+        }
+    }
+
+    Ok(median)
+}
+
+/// The last `records` prices of the tracked asset, most recent first.
+pub(crate) fn price_history(e: &Env, records: u32) -> Result<Vec<reflector::PriceData>, Error> {
+    let params = current_params(e)?;
+    let history = first_answer(e, &params, |source| source.prices(records))?;
+
+    let last = history.first().ok_or(Error::NoPrice)?;
+    check_age(e, &params.guards, &last)?;
+
+    Ok(history)
+}
+
+/// The price of the tracked asset recorded for the oracle timeframe containing `timestamp`, as read with the risk
+/// parameters of `period`.
+pub(crate) fn price_at(e: &Env, period: i32, timestamp: u64) -> Result<i128, Error> {
+    first_answer(e, &period_params(e, period)?, |source| {
+        source.price_at(timestamp).map(|data| data.price)
+    })
+}
+
+/// The prices of the tracked asset recorded after `from` and until `to` (inclusive), as read with the risk
+/// parameters of `period`, along with the timestamp the reading got to.
+/// At most `MAX_ORACLE_RECORDS` oracle timeframes are read per call: recent windows are read at once, older ones are
+/// walked forward one timeframe at a time. The window was read entirely when the returned timestamp is `to`.
+pub(crate) fn prices_between(
+    e: &Env,
+    period: i32,
+    from: u64,
+    to: u64,
+) -> Result<(Vec<i128>, u64), Error> {
+    if from > to {
+        return Ok((Vec::new(e), to));
+    }
+
+    let now = e.ledger().timestamp();
+
+    first_answer(e, &period_params(e, period)?, |source| {
+        let resolution = source.resolution()?;
+        let mut prices = Vec::new(e);
+
+        let records = now.saturating_sub(from) / resolution + 1;
+        if records <= MAX_ORACLE_RECORDS as u64 {
+            for record in source.prices(records as u32)?.iter() {
+                if record.timestamp >= from && record.timestamp <= to {
+                    prices.push_back(record.price);
+                }
+            }
+
+            return Some((prices, to));
+        }
+
+        // the first timeframe recorded after `from`
+        let mut timeframe = from.div_ceil(resolution) * resolution;
+        let mut until = from;
+
+        for _ in 0..MAX_ORACLE_RECORDS {
+            if timeframe > to {
+                return Some((prices, to));
+            }
+
+            // timeframes the oracle has no record for are skipped
+            if let Some(record) = source.price_at(timeframe) {
+                prices.push_back(record.price);
+            }
+
+            until = timeframe;
+            timeframe += resolution;
+        }
+
+        if timeframe > to {
+            until = to;
+        }
+
+        Some((prices, until))
+    })
+}
+
+/// The price of `asset` in `quote`, normalized to 7 decimals, aggregated over the oracle sources that know `asset`
+/// according to the pool's `OracleQuorum`. Simple feeds only know assets by symbol.
+pub(crate) fn conversion_rate(
+    e: &Env,
+    asset: &QuoteAsset,
+    quote: &QuoteAsset,
+) -> Result<i128, Error> {
+    let params = current_params(e)?;
+    let base = asset.clone().into_reflector();
+    let quote = Some(quote.clone().into_reflector());
+
+    let sources = params
+        .oracles
+        .iter()
+        .filter_map(|config| Source::for_asset(e, &config, &base, &quote));
+
+    quorum_price(e, &params, sources)
+}
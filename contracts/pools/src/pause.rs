@@ -0,0 +1,42 @@
+use crate::{
+    events,
+    storage::*,
+    types::{Error, PauseScope},
+};
+use soroban_sdk::{Env, Vec};
+
+/// Pauses each operation in `scopes`, cancelling its scheduled unpause if any.
+pub(crate) fn pause_scopes(e: &Env, scopes: Vec<PauseScope>) {
+    for scope in scopes.iter() {
+        put_paused(e, scope, true);
+        put_unpause_at(e, scope, None);
+
+        events::paused(e, scope);
+    }
+}
+
+/// Unpauses `scope`, or schedules its unpause on the first request when the pool has an unpause delay.
+pub(crate) fn unpause_scope(e: &Env, scope: PauseScope) -> Result<(), Error> {
+    if !is_paused(e, scope) {
+        return Ok(());
+    }
+
+    let delay = get_unpause_delay(e);
+    let ledger = e.ledger().sequence();
+    match get_unpause_at(e, scope) {
+        None if delay > 0 => {
+            put_unpause_at(e, scope, Some(ledger + delay));
+
+            events::unpause_scheduled(e, scope, ledger + delay);
+            return Ok(());
+        }
+        Some(unpause_at) if ledger < unpause_at => return Err(Error::Timelocked),
+        _ => {}
+    }
+
+    put_paused(e, scope, false);
+    put_unpause_at(e, scope, None);
+
+    events::unpaused(e, scope);
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::{
+    storage::{get_policy_nft, remove_token_policy, write_token_policy},
+    types::{Error, PolicyMetadata, PolicyRef},
+};
+use soroban_sdk::{contractclient, Address, Env};
+
+/// Interface of the companion contract the pool's policies are minted from.
+#[allow(dead_code)]
+#[contractclient(name = "PolicyNftClient")]
+pub trait PolicyNft {
+    /// Makes `minter` the only address that can mint and burn tokens, with its auth.
+    fn initialize(env: Env, minter: Address);
+
+    /// Mints a policy token to `to` and returns its id.
+    fn mint(env: Env, to: Address, metadata: PolicyMetadata) -> u64;
+
+    /// Burns a policy token.
+    fn burn(env: Env, token_id: u64);
+
+    /// The current owner of a policy token.
+    fn owner_of(env: Env, token_id: u64) -> Address;
+}
+
+/// Initializes the policy NFT contract of the pool with the pool as minter, so that the pool's
+/// initialization fails if someone else initialized it first.
+pub(crate) fn init_policy_nft(e: &Env, nft: &Address) -> Result<(), Error> {
+    match PolicyNftClient::new(e, nft).try_initialize(&e.current_contract_address()) {
+        Ok(Ok(())) => Ok(()),
+        _ => Err(Error::AlreadyInitialized),
+    }
+}
+
+/// Mints the token of `beneficiary`'s policy of `period` when the pool has a policy NFT contract.
+pub(crate) fn mint_policy(
+    e: &Env,
+    beneficiary: Address,
+    coverage: i128,
+    strike: i128,
+    period: i32,
+) -> Option<u64> {
+    let nft = get_policy_nft(e)?;
+
+    let token_id = PolicyNftClient::new(e, &nft).mint(
+        &beneficiary,
+        &PolicyMetadata {
+            pool: e.current_contract_address(),
+            period,
+            coverage,
+            strike,
+        },
+    );
+    write_token_policy(
+        e,
+        token_id,
+        PolicyRef {
+            beneficiary,
+            period,
+        },
+    );
+
+    Some(token_id)
+}
+
+/// Whoever a policy pays out to: the owner of its token if it has one, the beneficiary otherwise.
+pub(crate) fn policy_owner(e: &Env, beneficiary: Address, token: Option<u64>) -> Address {
+    match (token, get_policy_nft(e)) {
+        (Some(token_id), Some(nft)) => PolicyNftClient::new(e, &nft).owner_of(&token_id),
+        _ => beneficiary,
+    }
+}
+
+/// Burns the token of a policy that was paid out.
+pub(crate) fn burn_policy(e: &Env, token: Option<u64>) {
+    if let (Some(token_id), Some(nft)) = (token, get_policy_nft(e)) {
+        PolicyNftClient::new(e, &nft).burn(&token_id);
+        remove_token_policy(e, token_id);
+    }
+}
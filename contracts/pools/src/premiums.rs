@@ -0,0 +1,118 @@
+use crate::{
+    events,
+    math::rescale,
+    oracle::conversion_rate,
+    rewards::update_fee_per_share_universal,
+    storage::*,
+    token_utility::{get_token_client, transfer, transfer_in_pool},
+    types::{Error, PremiumToken},
+};
+use fixed_point_math::{FixedPoint, STROOP};
+use soroban_sdk::{token, Address, Env};
+
+// the allowlist entry of `token`
+fn premium_token(e: &Env, token: &Address) -> Result<PremiumToken, Error> {
+    get_premium_tokens(e)
+        .iter()
+        .find(|config| config.token == *token)
+        .ok_or(Error::TokenNotAccepted)
+}
+
+/// What `amount` of an allowlisted `token` is worth in the pool's token, net of its spread.
+pub(crate) fn premium_value(e: &Env, token: &Address, amount: i128) -> Result<i128, Error> {
+    let config = premium_token(e, token)?;
+    let token_asset = get_token_asset(e).ok_or(Error::TokenNotAccepted)?;
+    let rate = conversion_rate(e, &config.asset, &token_asset)?;
+
+    // the rate is normalized to 7 decimals, amounts are in each token's own decimals
+    let value = rescale(
+        amount.fixed_mul_floor(rate, STROOP.into()).unwrap(),
+        token::Client::new(e, token).decimals(),
+        get_token_client(e).decimals(),
+    );
+
+    Ok(value
+        .fixed_mul_floor(STROOP as i128 - config.spread, STROOP.into())
+        .unwrap())
+}
+
+/// The share of a premium of `amount` credited to the referrer of the subscription, in the premium's token.
+pub(crate) fn referral_commission(e: &Env, amount: i128) -> i128 {
+    amount
+        .fixed_mul_floor(get_referral_commission(e), STROOP.into())
+        .unwrap()
+}
+
+/// Collects a premium of `amount` of an allowlisted `token` from `payer`. The commission of the `referrer` is paid in
+/// `token` right away, the rest is kept aside for the liquidity providers of `period` until it's converted.
+pub(crate) fn collect_premium(
+    e: &Env,
+    payer: &Address,
+    token: &Address,
+    amount: i128,
+    referrer: Option<Address>,
+    period: i32,
+) {
+    let client = token::Client::new(e, token);
+    transfer_in_pool(e, &client, payer, &amount);
+
+    let commission = match referrer {
+        Some(referrer) => {
+            let commission = referral_commission(e, amount);
+            if commission > 0 {
+                transfer(e, &client, &referrer, &commission);
+                events::foreign_referral(e, referrer, token.clone(), commission, period);
+            }
+
+            commission
+        }
+        None => 0,
+    };
+
+    write_foreign_premiums(
+        e,
+        token.clone(),
+        read_foreign_premiums(e, token.clone(), period) + amount - commission,
+        period,
+    );
+}
+
+/// Sells `amount` of the premiums of `period` collected in `token` to `buyer` for the pool's token, at the same rate
+/// and spread premiums are accepted at. What `buyer` pays is credited to the liquidity providers of `period`.
+/// Returns the amount of the pool's token paid by `buyer`.
+pub(crate) fn convert_premiums(
+    e: &Env,
+    buyer: Address,
+    token: Address,
+    period: i32,
+    amount: i128,
+) -> Result<i128, Error> {
+    let collected = read_foreign_premiums(e, token.clone(), period);
+    if amount > collected {
+        return Err(Error::InvalidAmount);
+    }
+
+    // the period's liquidity providers can't leave before its premiums are converted, so it has some unless none
+    // ever deposited
+    if get_tot_supply(e, period) == 0 {
+        return Err(Error::NoBalance);
+    }
+
+    let price = premium_value(e, &token, amount)?;
+
+    transfer_in_pool(e, &get_token_client(e), &buyer, &price);
+    transfer(e, &token::Client::new(e, &token), &buyer, &amount);
+    write_foreign_premiums(e, token.clone(), collected - amount, period);
+
+    update_fee_per_share_universal(e, price, period);
+
+    events::premiums_converted(e, buyer, token, amount, price);
+    Ok(price)
+}
+
+/// Whether some premiums of `period`, collected in any of the tokens the pool accepts, weren't converted yet.
+pub(crate) fn has_foreign_premiums(e: &Env, period: i32) -> bool {
+    get_premium_tokens(e)
+        .iter()
+        .any(|config| read_foreign_premiums(e, config.token, period) > 0)
+}
@@ -0,0 +1,254 @@
+use crate::{
+    math::normalize_price,
+    reflector::{self, PriceData},
+    storage::get_oracle_decimals,
+    types::{Error, OracleKind, OracleSource},
+    PRICE_DECIMALS,
+};
+use fixed_point_math::FixedPoint;
+use soroban_sdk::{contractclient, symbol_short, Env, Symbol, Vec};
+
+/// Interface of a minimal price feed contract, for oracle providers other than Reflector.
+#[allow(dead_code)]
+#[contractclient(name = "SimpleFeedClient")]
+pub trait SimplePriceFeed {
+    /// The last price of `asset`, with the unix timestamp (in seconds) it was recorded at.
+    fn lastprice(env: Env, asset: Symbol) -> Option<PriceData>;
+
+    /// The number of decimals of the prices.
+    fn decimals(env: Env) -> u32;
+}
+
+// price of `base` in `quote`, keeping the `decimals` of both prices
+fn ratio(base: i128, quote: i128, decimals: u32) -> Option<i128> {
+    if quote <= 0 {
+        return None;
+    }
+
+    base.fixed_mul_floor(10_i128.pow(decimals), quote)
+}
+
+// a source that halted or returns malformed data is treated as not having a price
+fn answer<T, E, F>(result: Result<Result<T, E>, F>) -> Option<T> {
+    result.ok().and_then(|inner| inner.ok())
+}
+
+/// Read access to the prices of the asset tracked by the pool, or of the base/quote ratio for pair pools.
+/// Methods return `None` when the source can't answer, either because it halted or because it doesn't support the query.
+pub(crate) trait PriceSource {
+    /// The last price.
+    fn last_price(&self) -> Option<PriceData>;
+
+    /// The last `records` prices, most recent first.
+    fn prices(&self, records: u32) -> Option<Vec<PriceData>>;
+
+    /// The price recorded for the timeframe containing `timestamp`.
+    fn price_at(&self, timestamp: u64) -> Option<PriceData>;
+
+    /// The time between two records, in seconds.
+    fn resolution(&self) -> Option<u64>;
+}
+
+/// Pair prices use Reflector's cross-price functions.
+pub(crate) struct ReflectorSource<'a> {
+    client: reflector::Client<'a>,
+    asset: reflector::Asset,
+    quote: Option<reflector::Asset>,
+}
+
+impl PriceSource for ReflectorSource<'_> {
+    fn last_price(&self) -> Option<PriceData> {
+        match &self.quote {
+            Some(quote) => answer(self.client.try_x_last_price(&self.asset, quote)),
+            None => answer(self.client.try_lastprice(&self.asset)),
+        }
+        .flatten()
+    }
+
+    fn prices(&self, records: u32) -> Option<Vec<PriceData>> {
+        match &self.quote {
+            Some(quote) => answer(self.client.try_x_prices(&self.asset, quote, &records)),
+            None => answer(self.client.try_prices(&self.asset, &records)),
+        }
+        .flatten()
+    }
+
+    fn price_at(&self, timestamp: u64) -> Option<PriceData> {
+        let resolution = self.resolution()?;
+        let timeframe = timestamp - timestamp % resolution;
+
+        match &self.quote {
+            Some(quote) => answer(self.client.try_x_price(&self.asset, quote, &timeframe)),
+            None => answer(self.client.try_price(&self.asset, &timeframe)),
+        }
+        .flatten()
+    }
+
+    fn resolution(&self) -> Option<u64> {
+        answer(self.client.try_resolution()).map(|resolution| (resolution as u64).max(1))
+    }
+}
+
+/// Simple feeds only expose the last price, history based features are not available with them.
+/// Pair prices are computed from the last prices of both assets, which must be symbols known by the feed.
+pub(crate) struct SimpleFeedSource<'a> {
+    client: SimpleFeedClient<'a>,
+    asset: Symbol,
+    quote: Option<reflector::Asset>,
+    decimals: u32,
+}
+
+impl PriceSource for SimpleFeedSource<'_> {
+    fn last_price(&self) -> Option<PriceData> {
+        let base = answer(self.client.try_lastprice(&self.asset)).flatten()?;
+
+        match &self.quote {
+            None => Some(base),
+            Some(reflector::Asset::Other(symbol)) => {
+                let quote = answer(self.client.try_lastprice(symbol)).flatten()?;
+                let price = ratio(base.price, quote.price, self.decimals)?;
+
+                Some(PriceData {
+                    price,
+                    timestamp: base.timestamp.min(quote.timestamp),
+                })
+            }
+            Some(reflector::Asset::Stellar(_)) => None,
+        }
+    }
+
+    fn prices(&self, _records: u32) -> Option<Vec<PriceData>> {
+        None
+    }
+
+    fn price_at(&self, _timestamp: u64) -> Option<PriceData> {
+        None
+    }
+
+    fn resolution(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// The number of decimals of the prices reported by `config`, read from the oracle itself.
+pub(crate) fn query_decimals(e: &Env, config: &OracleSource) -> Result<u32, Error> {
+    let decimals = match config.kind {
+        OracleKind::Reflector => answer(reflector::Client::new(e, &config.address).try_decimals()),
+        OracleKind::SimpleFeed => answer(SimpleFeedClient::new(e, &config.address).try_decimals()),
+    };
+
+    decimals.ok_or(Error::NoPrice)
+}
+
+enum Adapter<'a> {
+    Reflector(ReflectorSource<'a>),
+    SimpleFeed(SimpleFeedSource<'a>),
+}
+
+/// A configured oracle source, dispatching to the adapter of its provider.
+/// Prices returned through `PriceSource` are normalized to `PRICE_DECIMALS`.
+pub(crate) struct Source<'a> {
+    adapter: Adapter<'a>,
+    decimals: u32,
+}
+
+impl<'a> Source<'a> {
+    pub(crate) fn new(
+        e: &'a Env,
+        config: &OracleSource,
+        asset: &reflector::Asset,
+        symbol: &Symbol,
+        quote: &Option<reflector::Asset>,
+    ) -> Self {
+        // sources configured before decimals were recorded are used with their raw prices
+        let decimals = get_oracle_decimals(e, config.address.clone()).unwrap_or(PRICE_DECIMALS);
+
+        let adapter = match config.kind {
+            OracleKind::Reflector => Adapter::Reflector(ReflectorSource {
+                client: reflector::Client::new(e, &config.address),
+                asset: asset.clone(),
+                quote: quote.clone(),
+            }),
+            OracleKind::SimpleFeed => Adapter::SimpleFeed(SimpleFeedSource {
+                client: SimpleFeedClient::new(e, &config.address),
+                asset: symbol.clone(),
+                quote: quote.clone(),
+                decimals,
+            }),
+        };
+
+        Self { adapter, decimals }
+    }
+
+    /// A source for `asset` alone, which simple feeds only know when it's a symbol.
+    pub(crate) fn for_asset(
+        e: &'a Env,
+        config: &OracleSource,
+        asset: &reflector::Asset,
+        quote: &Option<reflector::Asset>,
+    ) -> Option<Self> {
+        let symbol = match (config.kind, asset) {
+            (_, reflector::Asset::Other(symbol)) => symbol.clone(),
+            // Reflector sources read the asset itself, the symbol is only used by simple feeds
+            (OracleKind::Reflector, reflector::Asset::Stellar(_)) => symbol_short!(""),
+            (OracleKind::SimpleFeed, reflector::Asset::Stellar(_)) => return None,
+        };
+
+        Some(Self::new(e, config, asset, &symbol, quote))
+    }
+
+    pub(crate) fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    /// The last price as reported by the oracle.
+    pub(crate) fn raw_last_price(&self) -> Option<PriceData> {
+        match &self.adapter {
+            Adapter::Reflector(source) => source.last_price(),
+            Adapter::SimpleFeed(source) => source.last_price(),
+        }
+    }
+
+    fn normalize(&self, data: PriceData) -> PriceData {
+        PriceData {
+            price: normalize_price(data.price, self.decimals),
+            timestamp: data.timestamp,
+        }
+    }
+}
+
+impl PriceSource for Source<'_> {
+    fn last_price(&self) -> Option<PriceData> {
+        self.raw_last_price().map(|data| self.normalize(data))
+    }
+
+    fn prices(&self, records: u32) -> Option<Vec<PriceData>> {
+        let history = match &self.adapter {
+            Adapter::Reflector(source) => source.prices(records),
+            Adapter::SimpleFeed(source) => source.prices(records),
+        }?;
+
+        let mut normalized = Vec::new(history.env());
+        for data in history.iter() {
+            normalized.push_back(self.normalize(data));
+        }
+
+        Some(normalized)
+    }
+
+    fn price_at(&self, timestamp: u64) -> Option<PriceData> {
+        let data = match &self.adapter {
+            Adapter::Reflector(source) => source.price_at(timestamp),
+            Adapter::SimpleFeed(source) => source.price_at(timestamp),
+        }?;
+
+        Some(self.normalize(data))
+    }
+
+    fn resolution(&self) -> Option<u64> {
+        match &self.adapter {
+            Adapter::Reflector(source) => source.resolution(),
+            Adapter::SimpleFeed(source) => source.resolution(),
+        }
+    }
+}
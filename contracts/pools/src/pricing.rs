@@ -0,0 +1,31 @@
+use crate::{
+    math::{realized_volatility, volatility_scale},
+    oracle::price_history,
+    risk::current_params,
+    storage::get_pricing_model,
+    types::{Error, PayoutMode, PricingModel},
+};
+use fixed_point_math::{FixedPoint, STROOP};
+use soroban_sdk::Env;
+
+/// Returns the factor (7 decimals) applied to the multiplier part of the coverage.
+/// `STROOP` leaves the coverage unchanged.
+pub(crate) fn coverage_scale(e: &Env) -> Result<i128, Error> {
+    let model_scale = match get_pricing_model(e) {
+        PricingModel::Fixed => STROOP as i128,
+        PricingModel::RealizedVolatility(config) => {
+            let history = price_history(e, config.records)?;
+            let realized = realized_volatility(&history).ok_or(Error::NoPrice)?;
+
+            volatility_scale(realized, config.target, config.min_scale, config.max_scale)
+        }
+    };
+
+    // partial payouts are cheaper than the all-or-nothing cover the multiplier is set for
+    match current_params(e)?.payout {
+        PayoutMode::Binary => Ok(model_scale),
+        PayoutMode::Linear(config) => Ok(model_scale
+            .fixed_mul_floor(config.coverage_scale, STROOP.into())
+            .unwrap()),
+    }
+}
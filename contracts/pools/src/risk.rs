@@ -0,0 +1,114 @@
+use crate::{
+    checks::check_risk_params,
+    events,
+    math::{actual_period, calculate_period},
+    price_source::query_decimals,
+    storage::*,
+    types::{Error, PendingRiskParams, RiskParams},
+};
+use soroban_sdk::{Env, Vec};
+
+// the parameters currently stored in the instance
+fn active_params(e: &Env) -> Result<RiskParams, Error> {
+    Ok(RiskParams {
+        volatility: get_volatility(e)?,
+        multiplier: get_multiplier(e),
+        symbol: get_symbol(e),
+        oracles: get_oracles(e)?,
+        quorum: get_oracle_quorum(e),
+        guards: get_oracle_guards(e),
+        trigger: get_trigger_mode(e),
+        payout: get_payout_mode(e),
+    })
+}
+
+// replaces the active parameters with the pending ones once their period started
+fn activate_pending(e: &Env, period: i32) {
+    let Some(pending) = get_pending_risk_params(e) else {
+        return;
+    };
+
+    if period < pending.effective_period {
+        return;
+    }
+
+    put_volatility(e, pending.params.volatility);
+    put_multiplier(e, pending.params.multiplier);
+    put_symbol(e, pending.params.symbol.clone());
+    put_oracles(e, pending.params.oracles.clone());
+    put_oracle_quorum(e, pending.params.quorum.clone());
+    put_oracle_guards(e, pending.params.guards.clone());
+    put_trigger_mode(e, pending.params.trigger);
+    put_payout_mode(e, pending.params.payout.clone());
+    put_pending_risk_params(e, None);
+
+    // recorded only now, so that a proposal doesn't change how the live period's prices are read
+    for (source, decimals) in pending.params.oracles.iter().zip(pending.decimals.iter()) {
+        put_oracle_decimals(e, source.address, decimals);
+    }
+
+    events::risk_params_activated(e, pending.params, period);
+}
+
+/// The risk parameters of `period`. The current period's are snapshotted the first time they are read, after
+/// activating any pending change that is due, so that they can't change under its policies.
+pub(crate) fn period_params(e: &Env, period: i32) -> Result<RiskParams, Error> {
+    if let Some(params) = read_period_risk_params(e, period) {
+        return Ok(params);
+    }
+
+    // past periods without a snapshot never sold a policy
+    if period != actual_period(e) {
+        return active_params(e);
+    }
+
+    activate_pending(e, period);
+
+    let params = active_params(e)?;
+    write_period_risk_params(e, &params, period);
+
+    Ok(params)
+}
+
+pub(crate) fn current_params(e: &Env) -> Result<RiskParams, Error> {
+    period_params(e, actual_period(e))
+}
+
+/// The risk parameters the pool is heading to: the pending ones if a change is scheduled, the current ones
+/// otherwise. Changes to a single parameter are made on top of them.
+pub(crate) fn scheduled_params(e: &Env) -> Result<RiskParams, Error> {
+    match get_pending_risk_params(e) {
+        Some(pending) => Ok(pending.params),
+        None => active_params(e),
+    }
+}
+
+/// Schedules `params` to replace the current risk parameters from the first period starting after the pool's
+/// minimum delay. Returns that period.
+pub(crate) fn propose_params(e: &Env, params: RiskParams) -> Result<i32, Error> {
+    check_risk_params(&params)?;
+
+    let mut decimals = Vec::new(e);
+    for source in params.oracles.iter() {
+        decimals.push_back(query_decimals(e, &source)?);
+    }
+
+    let earliest = e.ledger().sequence() + get_risk_delay(e);
+    let effective_period = calculate_period(
+        earliest as i128,
+        get_genesis(e) as i128,
+        get_periods(e) as i128,
+    ) + 1;
+
+    put_pending_risk_params(
+        e,
+        Some(PendingRiskParams {
+            params: params.clone(),
+            effective_period,
+            decimals,
+        }),
+    );
+
+    events::risk_params_proposed(e, params, effective_period);
+    Ok(effective_period)
+}
@@ -1,7 +1,14 @@
-use soroban_sdk::{Address, Env, Symbol};
+use soroban_sdk::{symbol_short, vec, Address, Env, Map, Symbol, TryFromVal, Val, Vec};
 
 use crate::{
-    types::{BalanceObject, Error, InstanceDataKey, Insurance, PersistentDataKey},
+    reflector,
+    types::{
+        AggregationMode, BalanceObject, CoverageCaps, Error, GovernanceConfig, InstanceDataKey,
+        Insurance, LegacyInsurance, ObservedRange, OracleGuards, OracleKind, OracleQuorum,
+        OracleSource, PauseScope, PayoutMode, PendingRiskParams, PersistentDataKey, PolicyRef,
+        PolicyTerms, PremiumToken, PricingModel, Proposal, QuoteAsset, RiskParams, Role,
+        StrikeMode, SubscriptionCutoff, TriggerMode,
+    },
     INSTANCE_LEDGER_LIFE, INSTANCE_LEDGER_TTL_THRESHOLD, PERSISTENT_LEDGER_LIFE,
     PERSISTENT_LEDGER_TTL_THRESHOLD,
 };
@@ -35,6 +42,21 @@ pub(crate) fn read_balance(e: &Env, addr: Address, period: i32) -> i128 {
     }
 }
 
+// appends to `addr`'s deposits in `period` the ledger of a deposit, with the shares it held before it
+pub(crate) fn record_deposit(e: &Env, addr: Address, balance: i128, period: i32) {
+    let key = PersistentDataKey::DepositHistory(BalanceObject::new(addr.clone(), period));
+    let mut history = read_deposit_history(e, addr, period);
+    history.push_back((e.ledger().sequence(), balance));
+
+    e.storage().persistent().set(&key, &history);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_deposit_history(e: &Env, addr: Address, period: i32) -> Vec<(u32, i128)> {
+    let key = PersistentDataKey::DepositHistory(BalanceObject::new(addr, period));
+    e.storage().persistent().get(&key).unwrap_or(Vec::new(e))
+}
+
 pub(crate) fn write_fee_per_share_particular(e: &Env, addr: Address, amount: i128, period: i32) {
     let balance_object = BalanceObject::new(addr, period);
     let key = PersistentDataKey::FeePerShareParticular(balance_object);
@@ -78,13 +100,23 @@ pub(crate) fn write_refund_particular(
     addr: Address,
     amount: i128,
     price: i128,
+    terms: PolicyTerms,
+    token: Option<u64>,
     period: i32,
 ) {
     let balance_object = BalanceObject::new(addr, period);
     let key = PersistentDataKey::RefundParticular(balance_object);
-    e.storage()
-        .persistent()
-        .set(&key, &Insurance { amount, price });
+    let timestamp = e.ledger().timestamp();
+    e.storage().persistent().set(
+        &key,
+        &Insurance {
+            amount,
+            price,
+            timestamp,
+            terms,
+            token,
+        },
+    );
     bump_persistent(e, &key);
 }
 
@@ -92,7 +124,15 @@ pub(crate) fn read_refund_particular(e: &Env, addr: Address, period: i32) -> Opt
     let balance_object = BalanceObject::new(addr, period);
     let key = PersistentDataKey::RefundParticular(balance_object);
 
-    e.storage().persistent().get(&key)
+    // policies sold before the pool was upgraded keep their original layout, which has no terms
+    let fields: Map<Symbol, Val> = e.storage().persistent().get(&key)?;
+    if fields.contains_key(symbol_short!("terms")) {
+        Insurance::try_from_val(e, &fields.to_val()).ok()
+    } else {
+        LegacyInsurance::try_from_val(e, &fields.to_val())
+            .ok()
+            .map(Insurance::from)
+    }
 }
 
 pub(crate) fn has_refund_particular(e: &Env, addr: Address, period: i32) -> bool {
@@ -102,6 +142,143 @@ pub(crate) fn has_refund_particular(e: &Env, addr: Address, period: i32) -> bool
     e.storage().persistent().has(&key)
 }
 
+// coverage paid for by `addr` in `period`, whoever the beneficiaries are
+pub(crate) fn write_address_coverage(e: &Env, addr: Address, amount: i128, period: i32) {
+    let key = PersistentDataKey::AddressCoverage(BalanceObject::new(addr, period));
+    e.storage().persistent().set(&key, &amount);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_address_coverage(e: &Env, addr: Address, period: i32) -> i128 {
+    let key = PersistentDataKey::AddressCoverage(BalanceObject::new(addr, period));
+    e.storage().persistent().get(&key).unwrap_or(0)
+}
+
+// what a haircut payout left unpaid to `addr` for its policy of `period`
+pub(crate) fn write_shortfall(e: &Env, addr: Address, amount: i128, period: i32) {
+    let key = PersistentDataKey::Shortfall(BalanceObject::new(addr, period));
+    if amount == 0 {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+
+    e.storage().persistent().set(&key, &amount);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_shortfall(e: &Env, addr: Address, period: i32) -> i128 {
+    let key = PersistentDataKey::Shortfall(BalanceObject::new(addr, period));
+    e.storage().persistent().get(&key).unwrap_or(0)
+}
+
+// the price the policies of `period` are relative to in period strike mode
+pub(crate) fn write_reference_price(e: &Env, price: i128, period: i32) {
+    let key = PersistentDataKey::ReferencePrice(period);
+    e.storage().persistent().set(&key, &price);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_reference_price(e: &Env, period: i32) -> Option<i128> {
+    let key = PersistentDataKey::ReferencePrice(period);
+    e.storage().persistent().get(&key)
+}
+
+// distribution partners allowed to be credited referral commissions
+pub(crate) fn write_referrer(e: &Env, referrer: Address, registered: bool) {
+    let key = PersistentDataKey::Referrer(referrer);
+    if !registered {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+
+    e.storage().persistent().set(&key, &true);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn is_referrer(e: &Env, referrer: Address) -> bool {
+    let key = PersistentDataKey::Referrer(referrer);
+    e.storage().persistent().has(&key)
+}
+
+// commissions credited to `referrer` and not withdrawn yet
+pub(crate) fn write_referral_earnings(e: &Env, referrer: Address, amount: i128) {
+    let key = PersistentDataKey::ReferralEarnings(referrer);
+    e.storage().persistent().set(&key, &amount);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_referral_earnings(e: &Env, referrer: Address) -> i128 {
+    let key = PersistentDataKey::ReferralEarnings(referrer);
+    e.storage().persistent().get(&key).unwrap_or(0)
+}
+
+pub(crate) fn write_token_policy(e: &Env, token_id: u64, policy: PolicyRef) {
+    let key = PersistentDataKey::TokenPolicy(token_id);
+    e.storage().persistent().set(&key, &policy);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_token_policy(e: &Env, token_id: u64) -> Option<PolicyRef> {
+    let key = PersistentDataKey::TokenPolicy(token_id);
+    e.storage().persistent().get(&key)
+}
+
+pub(crate) fn remove_token_policy(e: &Env, token_id: u64) {
+    let key = PersistentDataKey::TokenPolicy(token_id);
+    e.storage().persistent().remove(&key);
+}
+
+// premiums of `period` received in `token` and not converted to the pool's token yet
+pub(crate) fn write_foreign_premiums(e: &Env, token: Address, amount: i128, period: i32) {
+    let key = PersistentDataKey::ForeignPremiums(BalanceObject::new(token, period));
+    e.storage().persistent().set(&key, &amount);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_foreign_premiums(e: &Env, token: Address, period: i32) -> i128 {
+    let key = PersistentDataKey::ForeignPremiums(BalanceObject::new(token, period));
+    e.storage().persistent().get(&key).unwrap_or(0)
+}
+
+// the risk parameters `period` was priced with
+pub(crate) fn write_period_risk_params(e: &Env, params: &RiskParams, period: i32) {
+    let key = PersistentDataKey::PeriodRiskParams(period);
+    e.storage().persistent().set(&key, params);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_period_risk_params(e: &Env, period: i32) -> Option<RiskParams> {
+    let key = PersistentDataKey::PeriodRiskParams(period);
+    e.storage().persistent().get(&key)
+}
+
+pub(crate) fn write_period_close(e: &Env, timestamp: u64, period: i32) {
+    let key = PersistentDataKey::PeriodClose(period);
+    e.storage().persistent().set(&key, &timestamp);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_period_close(e: &Env, period: i32) -> Option<u64> {
+    let key = PersistentDataKey::PeriodClose(period);
+    e.storage().persistent().get(&key)
+}
+
+pub(crate) fn write_observed_range(e: &Env, addr: Address, range: &ObservedRange, period: i32) {
+    let key = PersistentDataKey::ObservedRange(BalanceObject::new(addr, period));
+    e.storage().persistent().set(&key, range);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_observed_range(e: &Env, addr: Address, period: i32) -> Option<ObservedRange> {
+    let key = PersistentDataKey::ObservedRange(BalanceObject::new(addr, period));
+    e.storage().persistent().get(&key)
+}
+
+pub(crate) fn remove_observed_range(e: &Env, addr: Address, period: i32) {
+    let key = PersistentDataKey::ObservedRange(BalanceObject::new(addr, period));
+    e.storage().persistent().remove(&key);
+}
+
 pub(crate) fn write_refund_global(e: &Env, amount: i128, period: i32) {
     let key = PersistentDataKey::RefundGlobal(period);
     e.storage().persistent().set(&key, &amount);
@@ -160,6 +337,51 @@ pub(crate) fn bump_instance(env: &Env) {
         .extend_ttl(INSTANCE_LEDGER_TTL_THRESHOLD, INSTANCE_LEDGER_LIFE);
 }
 
+pub(crate) fn put_admin(e: &Env, admin: Address) {
+    let key = InstanceDataKey::Admin;
+    e.storage().instance().set(&key, &admin);
+}
+
+pub(crate) fn put_pending_admin(e: &Env, admin: Option<Address>) {
+    let key = InstanceDataKey::PendingAdmin;
+    if let Some(admin) = admin {
+        e.storage().instance().set(&key, &admin);
+    } else {
+        e.storage().instance().remove(&key);
+    }
+}
+
+pub(crate) fn get_pending_admin(e: &Env) -> Option<Address> {
+    let key = InstanceDataKey::PendingAdmin;
+    e.storage().instance().get(&key)
+}
+
+pub(crate) fn write_role_member(e: &Env, role: Role, addr: Address, member: bool) {
+    let key = PersistentDataKey::RoleMember(role, addr);
+    if !member {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+
+    e.storage().persistent().set(&key, &());
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn is_role_member(e: &Env, role: Role, addr: Address) -> bool {
+    let key = PersistentDataKey::RoleMember(role, addr);
+    e.storage().persistent().has(&key)
+}
+
+pub(crate) fn get_admin(e: &Env) -> Result<Address, Error> {
+    let key = InstanceDataKey::Admin;
+
+    if let Some(admin) = e.storage().instance().get(&key) {
+        Ok(admin)
+    } else {
+        return Err(Error::NotInitialized);
+    }
+}
+
 pub(crate) fn has_token_id(e: &Env) -> bool {
     let key = InstanceDataKey::TokenId;
     e.storage().instance().has(&key)
@@ -200,6 +422,53 @@ pub(crate) fn get_oracle_id(e: &Env) -> Result<Address, Error> {
     }
 }
 
+// oracle sources in fallback order
+pub(crate) fn put_oracles(e: &Env, oracles: Vec<OracleSource>) {
+    let key = InstanceDataKey::Oracles;
+    e.storage().instance().set(&key, &oracles);
+}
+
+// pools that never configured several sources use the oracle they were initialized with
+pub(crate) fn get_oracles(e: &Env) -> Result<Vec<OracleSource>, Error> {
+    let key = InstanceDataKey::Oracles;
+
+    if let Some(oracles) = e.storage().instance().get(&key) {
+        return Ok(oracles);
+    }
+
+    Ok(vec![
+        e,
+        OracleSource {
+            kind: OracleKind::Reflector,
+            address: get_oracle_id(e)?,
+        },
+    ])
+}
+
+pub(crate) fn put_oracle_decimals(e: &Env, oracle: Address, decimals: u32) {
+    let key = InstanceDataKey::OracleDecimals(oracle);
+    e.storage().instance().set(&key, &decimals);
+}
+
+pub(crate) fn get_oracle_decimals(e: &Env, oracle: Address) -> Option<u32> {
+    let key = InstanceDataKey::OracleDecimals(oracle);
+    e.storage().instance().get(&key)
+}
+
+pub(crate) fn put_oracle_quorum(e: &Env, quorum: OracleQuorum) {
+    let key = InstanceDataKey::OracleQuorum;
+    e.storage().instance().set(&key, &quorum);
+}
+
+pub(crate) fn get_oracle_quorum(e: &Env) -> OracleQuorum {
+    let key = InstanceDataKey::OracleQuorum;
+    e.storage().instance().get(&key).unwrap_or(OracleQuorum {
+        mode: AggregationMode::Median,
+        min_sources: 1,
+        max_divergence: 0,
+    })
+}
+
 pub(crate) fn put_volatility(e: &Env, amount: i128) {
     let key = InstanceDataKey::Volatility;
     e.storage().instance().set(&key, &amount);
@@ -282,4 +551,320 @@ pub(crate) fn put_oracle_asset(e: &Env, oracle_asset: Option<Address>) {
 pub(crate) fn get_oracle_asset(e: &Env) -> Option<Address> {
     let key = InstanceDataKey::OracleAsset;
     e.storage().instance().get(&key)
-}
\ No newline at end of file
+}
+
+// when set the pool follows the price of the tracked asset in this asset rather than in the oracle's base
+pub(crate) fn put_quote_asset(e: &Env, quote_asset: Option<reflector::Asset>) {
+    let key = InstanceDataKey::QuoteAsset;
+    e.storage().instance().set(&key, &quote_asset);
+}
+
+pub(crate) fn get_quote_asset(e: &Env) -> Option<reflector::Asset> {
+    let key = InstanceDataKey::QuoteAsset;
+    e.storage().instance().get(&key).flatten()
+}
+pub(crate) fn put_pricing_model(e: &Env, model: PricingModel) {
+    let key = InstanceDataKey::PricingModel;
+    e.storage().instance().set(&key, &model);
+}
+
+// pools created before pricing models were introduced keep the fixed multiplier
+pub(crate) fn get_pricing_model(e: &Env) -> PricingModel {
+    let key = InstanceDataKey::PricingModel;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(PricingModel::Fixed)
+}
+
+pub(crate) fn put_keeper_bounty(e: &Env, bounty: i128) {
+    let key = InstanceDataKey::KeeperBounty;
+    e.storage().instance().set(&key, &bounty);
+}
+
+pub(crate) fn get_keeper_bounty(e: &Env) -> i128 {
+    let key = InstanceDataKey::KeeperBounty;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+// ledgers after the end of a period during which its policies can still be claimed
+pub(crate) fn put_claim_grace(e: &Env, ledgers: u32) {
+    let key = InstanceDataKey::ClaimGrace;
+    e.storage().instance().set(&key, &ledgers);
+}
+
+pub(crate) fn get_claim_grace(e: &Env) -> u32 {
+    let key = InstanceDataKey::ClaimGrace;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub(crate) fn put_trigger_mode(e: &Env, mode: TriggerMode) {
+    let key = InstanceDataKey::TriggerMode;
+    e.storage().instance().set(&key, &mode);
+}
+
+pub(crate) fn get_trigger_mode(e: &Env) -> TriggerMode {
+    let key = InstanceDataKey::TriggerMode;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(TriggerMode::Spot)
+}
+
+pub(crate) fn put_oracle_guards(e: &Env, guards: OracleGuards) {
+    let key = InstanceDataKey::OracleGuards;
+    e.storage().instance().set(&key, &guards);
+}
+
+pub(crate) fn get_oracle_guards(e: &Env) -> OracleGuards {
+    let key = InstanceDataKey::OracleGuards;
+    e.storage().instance().get(&key).unwrap_or_default()
+}
+
+pub(crate) fn put_payout_mode(e: &Env, mode: PayoutMode) {
+    let key = InstanceDataKey::PayoutMode;
+    e.storage().instance().set(&key, &mode);
+}
+
+pub(crate) fn get_payout_mode(e: &Env) -> PayoutMode {
+    let key = InstanceDataKey::PayoutMode;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(PayoutMode::Binary)
+}
+
+pub(crate) fn put_policy_terms(e: &Env, terms: PolicyTerms) {
+    let key = InstanceDataKey::PolicyTerms;
+    e.storage().instance().set(&key, &terms);
+}
+
+pub(crate) fn get_policy_terms(e: &Env) -> PolicyTerms {
+    let key = InstanceDataKey::PolicyTerms;
+    e.storage().instance().get(&key).unwrap_or_default()
+}
+
+pub(crate) fn put_coverage_caps(e: &Env, caps: CoverageCaps) {
+    let key = InstanceDataKey::CoverageCaps;
+    e.storage().instance().set(&key, &caps);
+}
+
+pub(crate) fn get_coverage_caps(e: &Env) -> CoverageCaps {
+    let key = InstanceDataKey::CoverageCaps;
+    e.storage().instance().get(&key).unwrap_or_default()
+}
+
+pub(crate) fn put_pro_rata(e: &Env, enabled: bool) {
+    let key = InstanceDataKey::ProRata;
+    e.storage().instance().set(&key, &enabled);
+}
+
+pub(crate) fn get_pro_rata(e: &Env) -> bool {
+    let key = InstanceDataKey::ProRata;
+    e.storage().instance().get(&key).unwrap_or(false)
+}
+
+// tokens held to top up shortfalls, not part of any period's liquidity
+pub(crate) fn put_reserve_fund(e: &Env, amount: i128) {
+    let key = InstanceDataKey::ReserveFund;
+    e.storage().instance().set(&key, &amount);
+}
+
+pub(crate) fn get_reserve_fund(e: &Env) -> i128 {
+    let key = InstanceDataKey::ReserveFund;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub(crate) fn put_subscription_cutoff(e: &Env, cutoff: SubscriptionCutoff) {
+    let key = InstanceDataKey::SubscriptionCutoff;
+    e.storage().instance().set(&key, &cutoff);
+}
+
+pub(crate) fn get_subscription_cutoff(e: &Env) -> SubscriptionCutoff {
+    let key = InstanceDataKey::SubscriptionCutoff;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(SubscriptionCutoff::None)
+}
+
+pub(crate) fn put_strike_mode(e: &Env, mode: StrikeMode) {
+    let key = InstanceDataKey::StrikeMode;
+    e.storage().instance().set(&key, &mode);
+}
+
+pub(crate) fn get_strike_mode(e: &Env) -> StrikeMode {
+    let key = InstanceDataKey::StrikeMode;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(StrikeMode::Purchase)
+}
+
+pub(crate) fn put_referral_commission(e: &Env, commission: i128) {
+    let key = InstanceDataKey::ReferralCommission;
+    e.storage().instance().set(&key, &commission);
+}
+
+pub(crate) fn get_referral_commission(e: &Env) -> i128 {
+    let key = InstanceDataKey::ReferralCommission;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub(crate) fn put_policy_nft(e: &Env, nft: Address) {
+    let key = InstanceDataKey::PolicyNft;
+    e.storage().instance().set(&key, &nft);
+}
+
+pub(crate) fn get_policy_nft(e: &Env) -> Option<Address> {
+    let key = InstanceDataKey::PolicyNft;
+    e.storage().instance().get(&key)
+}
+
+pub(crate) fn put_premium_tokens(e: &Env, tokens: Vec<PremiumToken>) {
+    let key = InstanceDataKey::PremiumTokens;
+    e.storage().instance().set(&key, &tokens);
+}
+
+pub(crate) fn get_premium_tokens(e: &Env) -> Vec<PremiumToken> {
+    let key = InstanceDataKey::PremiumTokens;
+    e.storage().instance().get(&key).unwrap_or(vec![e])
+}
+
+// the pool's token, as understood by its oracles
+pub(crate) fn put_token_asset(e: &Env, asset: QuoteAsset) {
+    let key = InstanceDataKey::TokenAsset;
+    e.storage().instance().set(&key, &asset);
+}
+
+pub(crate) fn get_token_asset(e: &Env) -> Option<QuoteAsset> {
+    let key = InstanceDataKey::TokenAsset;
+    e.storage().instance().get(&key)
+}
+
+pub(crate) fn put_paused(e: &Env, scope: PauseScope, paused: bool) {
+    let key = InstanceDataKey::Paused(scope);
+    if paused {
+        e.storage().instance().set(&key, &());
+    } else {
+        e.storage().instance().remove(&key);
+    }
+}
+
+pub(crate) fn is_paused(e: &Env, scope: PauseScope) -> bool {
+    let key = InstanceDataKey::Paused(scope);
+    e.storage().instance().has(&key)
+}
+
+// ledgers between the request to unpause a scope and the moment it can be unpaused
+pub(crate) fn put_unpause_delay(e: &Env, ledgers: u32) {
+    let key = InstanceDataKey::UnpauseDelay;
+    e.storage().instance().set(&key, &ledgers);
+}
+
+pub(crate) fn get_unpause_delay(e: &Env) -> u32 {
+    let key = InstanceDataKey::UnpauseDelay;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub(crate) fn put_unpause_at(e: &Env, scope: PauseScope, ledger: Option<u32>) {
+    let key = InstanceDataKey::UnpauseAt(scope);
+    if let Some(ledger) = ledger {
+        e.storage().instance().set(&key, &ledger);
+    } else {
+        e.storage().instance().remove(&key);
+    }
+}
+
+pub(crate) fn get_unpause_at(e: &Env, scope: PauseScope) -> Option<u32> {
+    let key = InstanceDataKey::UnpauseAt(scope);
+    e.storage().instance().get(&key)
+}
+
+pub(crate) fn put_pending_risk_params(e: &Env, pending: Option<PendingRiskParams>) {
+    let key = InstanceDataKey::PendingRiskParams;
+    if let Some(pending) = pending {
+        e.storage().instance().set(&key, &pending);
+    } else {
+        e.storage().instance().remove(&key);
+    }
+}
+
+pub(crate) fn get_pending_risk_params(e: &Env) -> Option<PendingRiskParams> {
+    let key = InstanceDataKey::PendingRiskParams;
+    e.storage().instance().get(&key)
+}
+
+// minimum number of ledgers between the proposal of new risk parameters and the start of the period they apply to
+pub(crate) fn put_risk_delay(e: &Env, ledgers: u32) {
+    let key = InstanceDataKey::RiskDelay;
+    e.storage().instance().set(&key, &ledgers);
+}
+
+pub(crate) fn get_risk_delay(e: &Env) -> u32 {
+    let key = InstanceDataKey::RiskDelay;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+// the schema of the data in storage, pools deployed before it was recorded are at version 0
+pub(crate) fn put_version(e: &Env, version: u32) {
+    let key = InstanceDataKey::Version;
+    e.storage().instance().set(&key, &version);
+}
+
+pub(crate) fn get_version(e: &Env) -> u32 {
+    let key = InstanceDataKey::Version;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub(crate) fn put_governance(e: &Env, config: Option<GovernanceConfig>) {
+    let key = InstanceDataKey::Governance;
+    e.storage().instance().set(&key, &config);
+}
+
+pub(crate) fn get_governance(e: &Env) -> Option<GovernanceConfig> {
+    let key = InstanceDataKey::Governance;
+    e.storage().instance().get(&key).unwrap_or(None)
+}
+
+// returns the id of the next proposal
+pub(crate) fn next_proposal_id(e: &Env) -> u32 {
+    let key = InstanceDataKey::ProposalCount;
+    let id: u32 = e.storage().instance().get(&key).unwrap_or(0);
+    e.storage().instance().set(&key, &(id + 1));
+    id
+}
+
+pub(crate) fn write_proposal(e: &Env, id: u32, proposal: &Proposal) {
+    let key = PersistentDataKey::Proposal(id);
+    e.storage().persistent().set(&key, proposal);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn read_proposal(e: &Env, id: u32) -> Option<Proposal> {
+    let key = PersistentDataKey::Proposal(id);
+    e.storage().persistent().get(&key)
+}
+
+// the weight `voter` voted on proposal `id` with
+pub(crate) fn write_vote(e: &Env, id: u32, voter: Address, weight: i128) {
+    let key = PersistentDataKey::Vote(id, voter);
+    e.storage().persistent().set(&key, &weight);
+    bump_persistent(e, &key);
+}
+
+pub(crate) fn has_voted(e: &Env, id: u32, voter: Address) -> bool {
+    let key = PersistentDataKey::Vote(id, voter);
+    e.storage().persistent().has(&key)
+}
+
+// the last period the pool was used in, and the timestamp of the last ledger it was used at
+pub(crate) fn put_last_observed(e: &Env, period: i32, timestamp: u64) {
+    let key = InstanceDataKey::LastObserved;
+    e.storage().instance().set(&key, &(period, timestamp));
+}
+
+pub(crate) fn get_last_observed(e: &Env) -> Option<(i32, u64)> {
+    let key = InstanceDataKey::LastObserved;
+    e.storage().instance().get(&key)
+}
@@ -0,0 +1,351 @@
+use crate::{
+    checks::{check_amount_gt_0, check_coverage_caps, check_not_paused, check_version},
+    claims::observe_period,
+    events,
+    math::{
+        actual_period, apply_terms, calculate_refund, find_x, gross_coverage, period_end,
+        relative_move,
+    },
+    oracle::last_price,
+    policy_nft::mint_policy,
+    premiums::{collect_premium, premium_value, referral_commission},
+    pricing::coverage_scale,
+    rewards::update_fee_per_share_universal,
+    risk::current_params,
+    storage::*,
+    token_utility::{get_token_client, transfer_in_pool},
+    types::{Error, PauseScope, PayoutMode, PolicyTerms, StrikeMode, SubscriptionCutoff},
+};
+use fixed_point_math::{FixedPoint, STROOP};
+use soroban_sdk::{Address, Env, Vec};
+
+/// The last ledger of `period` at which policies can still be bought.
+pub(crate) fn subscription_open_until(e: &Env, period: i32) -> i32 {
+    let cutoff = match get_subscription_cutoff(e) {
+        SubscriptionCutoff::None => 0,
+        SubscriptionCutoff::Ledgers(ledgers) => ledgers as i32,
+        SubscriptionCutoff::Fraction(fraction) => (get_periods(e) as i128)
+            .fixed_mul_ceil(fraction, STROOP.into())
+            .unwrap() as i32,
+    };
+
+    period_end(e, period) - cutoff
+}
+
+/// The reference price of the current period, recorded from the oracle the first time it's needed.
+pub(crate) fn open_period(e: &Env) -> Result<i128, Error> {
+    let current_period = actual_period(e);
+    if let Some(price) = read_reference_price(e, current_period) {
+        return Ok(price);
+    }
+
+    let price = last_price(e)?;
+    write_reference_price(e, price, current_period);
+
+    events::period_opened(e, price, current_period);
+    Ok(price)
+}
+
+/// The price a policy bought now is centered on, given the current oracle `price`.
+fn strike_price(e: &Env, price: i128) -> Result<i128, Error> {
+    let max_pre_move = match get_strike_mode(e) {
+        StrikeMode::Purchase => return Ok(price),
+        StrikeMode::Period(max_pre_move) => max_pre_move,
+    };
+
+    let reference = open_period(e)?;
+
+    // once the price got this close to triggering, buying cover is no longer a bet on the period
+    let params = current_params(e)?;
+    let moved = match params.payout {
+        PayoutMode::Binary => {
+            (price - reference).abs()
+                > params
+                    .volatility
+                    .fixed_mul_floor(max_pre_move, STROOP.into())
+                    .unwrap()
+        }
+        PayoutMode::Linear(config) => {
+            relative_move(reference, price).ok_or(Error::NoPrice)?
+                > config
+                    .attachment
+                    .fixed_mul_floor(max_pre_move, STROOP.into())
+                    .unwrap()
+        }
+    };
+
+    if moved {
+        return Err(Error::PriceAlreadyMoved);
+    }
+
+    Ok(reference)
+}
+
+// makes sure `beneficiary` can still be sold a policy for `period`
+fn check_open(e: &Env, beneficiary: &Address, period: i32) -> Result<(), Error> {
+    // late buyers may already know how the period ends
+    if e.ledger().sequence() as i32 > subscription_open_until(e, period) {
+        return Err(Error::SubscriptionClosed);
+    }
+
+    // check the beneficiary doesn't have an insurance yet for the current period
+    if has_refund_particular(e, beneficiary.clone(), period) {
+        return Err(Error::AlreadySubscribed);
+    }
+
+    Ok(())
+}
+
+// the coverage a `premium` buys now and the pool's liability for it
+fn policy_coverage(
+    premium: i128,
+    time_to_end: i32,
+    multiplier: i32,
+    scale: i128,
+    terms: &PolicyTerms,
+) -> (i128, i128) {
+    // this is the refund that the policy holder would receive if the triggering condition verifies
+    // - calculated as y = amount * (1 + (1 / (q * time_to_end)))
+    // - the greater q, the greater the differential in refund prize if enter later
+    // - q is scaled according to the pool's pricing model
+    let possible_amount_to_refund =
+        calculate_refund(time_to_end as i128, premium, multiplier as i128, scale);
+
+    // the buyer is credited part of the loss it takes on, the most the policy can pay still drops below the refund
+    let coverage = gross_coverage(possible_amount_to_refund, terms);
+    (coverage, apply_terms(coverage, terms))
+}
+
+// records `beneficiary`'s policy, minting its token when the pool has a policy NFT contract
+fn write_policy(
+    e: &Env,
+    beneficiary: &Address,
+    coverage: i128,
+    strike: i128,
+    terms: PolicyTerms,
+    period: i32,
+) {
+    let token = mint_policy(e, beneficiary.clone(), coverage, strike, period);
+
+    write_refund_particular(
+        e,
+        beneficiary.clone(),
+        coverage,
+        strike,
+        terms,
+        token,
+        period,
+    );
+}
+
+// credits `referrer` its commission on a `premium` paid in the pool's token, returns the commission
+fn credit_referrer(e: &Env, referrer: Address, premium: i128, period: i32) -> i128 {
+    let commission = referral_commission(e, premium);
+
+    if commission > 0 {
+        let earnings = read_referral_earnings(e, referrer.clone());
+        write_referral_earnings(e, referrer.clone(), earnings + commission);
+        events::referral(e, referrer, commission, period);
+    }
+
+    commission
+}
+
+/// Sells `beneficiary` a policy for the current period against a premium of `amount` paid by `payer`, in the pool's
+/// token or in an allowlisted `premium_token`.
+/// When the sale was brought by a `referrer`, its commission is taken from the fees of liquidity providers. Premiums
+/// paid in another token only become fees once they're converted, their commission is paid in that token.
+/// The deductible and co-insurance in `terms` lower what the policy pays, and so the pool's liability. Part of the
+/// reduction is credited to the buyer as a larger coverage for the same premium.
+pub(crate) fn subscribe_policy(
+    e: &Env,
+    payer: Address,
+    beneficiary: Address,
+    premium_token: Option<Address>,
+    amount: i128,
+    terms: PolicyTerms,
+    referrer: Option<Address>,
+) -> Result<(), Error> {
+    check_not_paused(e, PauseScope::Subscriptions)?;
+    check_version(e)?;
+
+    observe_period(e);
+
+    let current_period: i32 = actual_period(e);
+
+    // only partners registered by the treasury earn commissions, buyers can't refer themselves
+    if let Some(referrer) = &referrer {
+        if !is_referrer(e, referrer.clone()) || *referrer == payer || *referrer == beneficiary {
+            return Err(Error::InvalidReferrer);
+        }
+    }
+
+    check_open(e, &beneficiary, current_period)?;
+
+    // only the payer authorizes the subscription, the beneficiary is the one who can claim
+    payer.require_auth();
+
+    // the premium in the pool's token, which coverage and fees are computed from
+    let premium = match &premium_token {
+        Some(token) => premium_value(e, token, amount)?,
+        None => amount,
+    };
+
+    let tot_liquidity = get_tot_liquidity(e, current_period);
+    let refund_global = read_refund_global(e, current_period);
+
+    let (coverage, reserved) = policy_coverage(
+        premium,
+        find_x(e, current_period),
+        current_params(e)?.multiplier,
+        coverage_scale(e)?,
+        &terms,
+    );
+
+    if refund_global + reserved > tot_liquidity {
+        return Err(Error::NotEnoughLiquidity);
+    }
+
+    // caps bound what each beneficiary holds, whoever pays for it
+    let address_coverage = read_address_coverage(e, beneficiary.clone(), current_period);
+    check_coverage_caps(
+        &get_coverage_caps(e),
+        reserved,
+        address_coverage,
+        tot_liquidity,
+    )?;
+
+    // the price the policy's band is centered on, checked before any funds move
+    let reflector_price = strike_price(e, last_price(e)?)?;
+
+    match &premium_token {
+        // fees are credited once the premium is converted to the pool's token
+        Some(token) => collect_premium(e, &payer, token, amount, referrer, current_period),
+        None => {
+            transfer_in_pool(e, &get_token_client(e), &payer, &amount);
+
+            let commission = match referrer {
+                Some(referrer) => credit_referrer(e, referrer, premium, current_period),
+                None => 0,
+            };
+
+            update_fee_per_share_universal(e, premium - commission, current_period);
+        }
+    }
+
+    write_policy(
+        e,
+        &beneficiary,
+        coverage,
+        reflector_price,
+        terms,
+        current_period,
+    );
+
+    write_refund_global(e, refund_global + reserved, current_period);
+    write_address_coverage(
+        e,
+        beneficiary.clone(),
+        address_coverage + reserved,
+        current_period,
+    );
+
+    // bump_instance(&e);
+
+    if payer != beneficiary {
+        events::policy_sponsored(e, payer, beneficiary.clone(), current_period);
+    }
+
+    events::policy_purchase(e, beneficiary, premium, current_period);
+    Ok(())
+}
+
+/// Sells a policy for the current period to each beneficiary in `entries`, against the premium paired with it.
+/// `payer` pays every premium in a single transfer, all the policies share the same oracle price and the pool's
+/// capacity is checked once for the whole batch: if any policy can't be sold, none is.
+pub(crate) fn subscribe_batch(
+    e: &Env,
+    payer: Address,
+    entries: Vec<(Address, i128)>,
+) -> Result<(), Error> {
+    if entries.is_empty() {
+        return Err(Error::InvalidAmount);
+    }
+
+    check_not_paused(e, PauseScope::Subscriptions)?;
+    check_version(e)?;
+
+    observe_period(e);
+
+    let current_period: i32 = actual_period(e);
+
+    payer.require_auth();
+
+    let tot_liquidity = get_tot_liquidity(e, current_period);
+    let refund_global = read_refund_global(e, current_period);
+
+    let time_to_end = find_x(e, current_period);
+    let multiplier = current_params(e)?.multiplier;
+    let scale = coverage_scale(e)?;
+    let terms = get_policy_terms(e);
+    let caps = get_coverage_caps(e);
+
+    let reflector_price = strike_price(e, last_price(e)?)?;
+
+    let mut total_premium = 0;
+    let mut total_reserved = 0;
+    let mut policies: Vec<(Address, i128, i128, i128, i128)> = Vec::new(e);
+
+    // every policy is priced and checked before any is written
+    for (beneficiary, premium) in entries.iter() {
+        check_amount_gt_0(premium)?;
+        check_open(e, &beneficiary, current_period)?;
+
+        if policies.iter().any(|policy| policy.0 == beneficiary) {
+            return Err(Error::AlreadySubscribed);
+        }
+
+        let (coverage, reserved) = policy_coverage(premium, time_to_end, multiplier, scale, &terms);
+        let address_coverage = read_address_coverage(e, beneficiary.clone(), current_period);
+        check_coverage_caps(&caps, reserved, address_coverage, tot_liquidity)?;
+
+        total_premium += premium;
+        total_reserved += reserved;
+        policies.push_back((beneficiary, premium, coverage, reserved, address_coverage));
+    }
+
+    if refund_global + total_reserved > tot_liquidity {
+        return Err(Error::NotEnoughLiquidity);
+    }
+
+    transfer_in_pool(e, &get_token_client(e), &payer, &total_premium);
+
+    update_fee_per_share_universal(e, total_premium, current_period);
+
+    write_refund_global(e, refund_global + total_reserved, current_period);
+
+    for (beneficiary, premium, coverage, reserved, address_coverage) in policies.iter() {
+        write_policy(
+            e,
+            &beneficiary,
+            coverage,
+            reflector_price,
+            terms.clone(),
+            current_period,
+        );
+        write_address_coverage(
+            e,
+            beneficiary.clone(),
+            address_coverage + reserved,
+            current_period,
+        );
+
+        if payer != beneficiary {
+            events::policy_sponsored(e, payer.clone(), beneficiary.clone(), current_period);
+        }
+
+        events::policy_purchase(e, beneficiary, premium, current_period);
+    }
+
+    Ok(())
+}
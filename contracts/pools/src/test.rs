@@ -2,12 +2,23 @@ use fixed_point_math::STROOP;
 use soroban_sdk::{
     contract, contractimpl, contracttype,
     testutils::{Address as _, Ledger},
-    token, Address, Env, symbol_short
+    token, vec, Address, BytesN, Env, symbol_short, Vec
 };
 
 use crate::{
     contract::{Pool, PoolClient},
-    reflector::Asset,
+    math::{
+        apply_terms, gross_coverage, linear_payout, median, normalize_price, realized_volatility,
+        rescale,
+    },
+    reflector::{self, Asset},
+    storage::{put_tot_liquidity, put_version, write_refund_global},
+    types::{
+        AggregationMode, AssetPair, BalanceObject, CoverageCaps, Error, GovernanceConfig,
+        LegacyInsurance, LinearPayout, OracleGuards, OracleKind, OracleQuorum, OracleSource,
+        PairQuote, PauseScope, PayoutMode, PersistentDataKey, PolicyTerms, PremiumToken,
+        ProposalAction, QuoteAsset, Role, StrikeMode, SubscriptionCutoff, TrackedAsset, TriggerMode,
+    },
     DAY_IN_LEDGERS,
 };
 
@@ -40,6 +51,197 @@ mod mock_20 {
         }
     }
 }
+mod mock_history {
+    use super::PriceData;
+    use crate::reflector::Asset;
+    use fixed_point_math::STROOP;
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env, Vec};
+
+    pub const RESOLUTION: u64 = 300;
+
+    /// Reflector-like oracle recording a price for every timeframe, the price of a timeframe being the last one set
+    /// before it started.
+    #[contract]
+    pub struct PriceHistoryMock;
+
+    fn changes(env: &Env) -> Vec<PriceData> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("CHANGES"))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn price_in_effect(env: &Env, timeframe: u64) -> Option<PriceData> {
+        let mut price = None;
+        for change in changes(env).iter() {
+            if change.timestamp <= timeframe {
+                price = Some(change.price);
+            }
+        }
+
+        price.map(|price| PriceData {
+            price,
+            timestamp: timeframe,
+        })
+    }
+
+    // the oracle stops publishing once `stop()` is called
+    fn current_timeframe(env: &Env) -> u64 {
+        let now = env.ledger().timestamp();
+        let timeframe = now - now % RESOLUTION;
+
+        env.storage()
+            .instance()
+            .get(&symbol_short!("STOPPED"))
+            .map_or(timeframe, |stopped: u64| stopped.min(timeframe))
+    }
+
+    #[contractimpl]
+    impl PriceHistoryMock {
+        pub fn set_price(env: Env, price: i128) {
+            let mut changes = changes(&env);
+            changes.push_back(PriceData {
+                price,
+                timestamp: env.ledger().timestamp(),
+            });
+
+            env.storage()
+                .instance()
+                .set(&symbol_short!("CHANGES"), &changes);
+        }
+
+        pub fn stop(env: Env) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("STOPPED"), &current_timeframe(&env));
+        }
+
+        pub fn set_decimals(env: Env, decimals: u32) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("DECIMALS"), &decimals);
+        }
+
+        pub fn decimals(env: Env) -> u32 {
+            env.storage()
+                .instance()
+                .get(&symbol_short!("DECIMALS"))
+                .unwrap_or(7)
+        }
+
+        pub fn resolution(_env: Env) -> u32 {
+            RESOLUTION as u32
+        }
+
+        pub fn lastprice(env: Env, _asset: Asset) -> Option<PriceData> {
+            if env.storage().instance().has(&symbol_short!("QUOTE")) {
+                return None;
+            }
+
+            price_in_effect(&env, current_timeframe(&env))
+        }
+
+        // prices set are those of the base asset in `quote`, there's no price in the oracle's base asset anymore
+        pub fn set_quote(env: Env, quote: Asset) {
+            env.storage().instance().set(&symbol_short!("QUOTE"), &quote);
+        }
+
+        // cross prices in the quote asset follow the prices set, every other one is 1 for conversions between tokens
+        pub fn x_last_price(env: Env, _base: Asset, quote: Asset) -> Option<PriceData> {
+            if env.storage().instance().get(&symbol_short!("QUOTE")) == Some(quote) {
+                return price_in_effect(&env, current_timeframe(&env));
+            }
+
+            Some(PriceData {
+                price: STROOP as i128,
+                timestamp: current_timeframe(&env),
+            })
+        }
+
+        pub fn price(env: Env, _asset: Asset, timestamp: u64) -> Option<PriceData> {
+            let timeframe = timestamp - timestamp % RESOLUTION;
+            if timeframe > current_timeframe(&env) {
+                return None;
+            }
+
+            price_in_effect(&env, timeframe)
+        }
+
+        pub fn prices(env: Env, _asset: Asset, records: u32) -> Option<Vec<PriceData>> {
+            let mut prices = Vec::new(&env);
+            let mut timeframe = current_timeframe(&env);
+
+            while prices.len() < records {
+                match price_in_effect(&env, timeframe) {
+                    Some(data) => prices.push_back(data),
+                    None => break,
+                }
+
+                if timeframe < RESOLUTION {
+                    break;
+                }
+                timeframe -= RESOLUTION;
+            }
+
+            if prices.is_empty() {
+                None
+            } else {
+                Some(prices)
+            }
+        }
+    }
+}
+mod mock_nft {
+    use crate::types::{Error, PolicyMetadata};
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env};
+
+    /// Policy NFT contract only keeping track of the owners of the tokens.
+    #[contract]
+    pub struct PolicyNftMock;
+
+    #[contractimpl]
+    impl PolicyNftMock {
+        pub fn initialize(env: Env, minter: Address) -> Result<(), Error> {
+            minter.require_auth();
+            if env.storage().instance().has(&symbol_short!("MINTER")) {
+                return Err(Error::AlreadyInitialized);
+            }
+            env.storage().instance().set(&symbol_short!("MINTER"), &minter);
+            Ok(())
+        }
+
+        pub fn minter(env: Env) -> Address {
+            env.storage().instance().get(&symbol_short!("MINTER")).unwrap()
+        }
+
+        pub fn mint(env: Env, to: Address, _metadata: PolicyMetadata) -> u64 {
+            let token_id: u64 = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("NEXT"))
+                .unwrap_or(0);
+
+            env.storage()
+                .instance()
+                .set(&symbol_short!("NEXT"), &(token_id + 1));
+            env.storage().persistent().set(&token_id, &to);
+
+            token_id
+        }
+
+        pub fn burn(env: Env, token_id: u64) {
+            env.storage().persistent().remove(&token_id);
+        }
+
+        pub fn owner_of(env: Env, token_id: u64) -> Address {
+            env.storage().persistent().get(&token_id).unwrap()
+        }
+
+        pub fn transfer(env: Env, token_id: u64, to: Address) {
+            env.storage().persistent().set(&token_id, &to);
+        }
+    }
+}
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 // The price data for an asset at a given timestamp.
@@ -328,4 +530,1497 @@ fn refund() {
     assert_eq!(token_client.balance(&user3), 2030000000)
 }
 
-*/
\ No newline at end of file
+*/
+#[test]
+fn terms_lower_the_payout() {
+    let refund = 1000 * STROOP as i128;
+
+    let none = PolicyTerms::default();
+    assert_eq!(gross_coverage(refund, &none), refund);
+    assert_eq!(apply_terms(refund, &none), refund);
+
+    // half of the 40% co-insurance is credited: the coverage grows by 25% and the policy pays 75% of the refund
+    let coinsured = PolicyTerms {
+        deductible: 0,
+        coinsurance: 4_000_000,
+    };
+    let coverage = gross_coverage(refund, &coinsured);
+    assert_eq!(coverage, 1250 * STROOP as i128);
+    assert_eq!(apply_terms(coverage, &coinsured), 750 * STROOP as i128);
+
+    // half of the deductible is credited back as coverage
+    let deductible = PolicyTerms {
+        deductible: 100 * STROOP as i128,
+        coinsurance: 0,
+    };
+    let coverage = gross_coverage(refund, &deductible);
+    assert_eq!(coverage, 1050 * STROOP as i128);
+    assert_eq!(apply_terms(coverage, &deductible), 950 * STROOP as i128);
+}
+
+#[test]
+fn apply_terms_never_pays_below_the_deductible() {
+    let terms = PolicyTerms {
+        deductible: 100 * STROOP as i128,
+        coinsurance: 2_000_000,
+    };
+
+    assert_eq!(apply_terms(50 * STROOP as i128, &terms), 0);
+    assert_eq!(apply_terms(100 * STROOP as i128, &terms), 0);
+    assert_eq!(
+        apply_terms(600 * STROOP as i128, &terms),
+        400 * STROOP as i128
+    );
+}
+
+const VOLATILITY: i128 = 2 * STROOP as i128;
+
+struct Setup<'a> {
+    env: Env,
+    admin: Address,
+    pool: PoolClient<'a>,
+    oracle: mock_history::PriceHistoryMockClient<'a>,
+    token: token::Client<'a>,
+    token_admin: token::StellarAssetClient<'a>,
+    nft: mock_nft::PolicyNftMockClient<'a>,
+    lp: Address,
+}
+
+// advances the ledger keeping the 5 seconds close time of Stellar ledgers
+fn advance(env: &Env, ledgers: u32) {
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number += ledgers;
+        ledger.timestamp += 5 * ledgers as u64;
+    });
+}
+
+// a one day pool on an asset priced at 20, with 1000 deposited by a liquidity provider
+fn setup<'a>() -> Setup<'a> {
+    setup_with(|_| {})
+}
+
+// same as `setup()`, starting a period after `configure` scheduled its risk parameters
+fn setup_with<'a>(configure: impl FnOnce(&Setup)) -> Setup<'a> {
+    build(false, PairQuote::None, configure)
+}
+
+// same as `setup()`, minting policies from `Setup::nft`
+fn setup_nft<'a>() -> Setup<'a> {
+    build(true, PairQuote::None, |_| {})
+}
+
+// same as `setup()`, on the price of the asset in USDC
+fn setup_pair<'a>() -> Setup<'a> {
+    build(false, PairQuote::Other(symbol_short!("USDC")), |_| {})
+}
+
+fn build<'a>(policy_nft: bool, quote: PairQuote, configure: impl FnOnce(&Setup)) -> Setup<'a> {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = 1000;
+        ledger.timestamp = 1_700_000_000;
+    });
+
+    let admin = Address::generate(&env);
+    let token_id = env.register_stellar_asset_contract(admin.clone());
+    let token = token::Client::new(&env, &token_id);
+    let token_admin = token::StellarAssetClient::new(&env, &token_id);
+
+    let oracle_addr = env.register_contract(&None, mock_history::PriceHistoryMock);
+    let oracle = mock_history::PriceHistoryMockClient::new(&env, &oracle_addr);
+    oracle.set_price(&(20 * STROOP as i128));
+    if let Some(quote) = quote.clone().into_reflector() {
+        oracle.set_quote(&quote);
+    }
+
+    let nft_addr = env.register_contract(&None, mock_nft::PolicyNftMock);
+    let nft = mock_nft::PolicyNftMockClient::new(&env, &nft_addr);
+
+    let pool_addr = env.register_contract(&None, Pool);
+    let pool = PoolClient::new(&env, &pool_addr);
+    pool.initialize(
+        &admin,
+        &token_id,
+        &oracle_addr,
+        &AssetPair {
+            symbol: symbol_short!("XLM"),
+            base: TrackedAsset::External,
+            quote,
+        },
+        &1,
+        &VOLATILITY,
+        &3,
+        &policy_nft.then_some(nft_addr),
+    );
+
+    let lp = Address::generate(&env);
+    let setup = Setup {
+        env,
+        admin,
+        pool,
+        oracle,
+        token,
+        token_admin,
+        nft,
+        lp,
+    };
+
+    configure(&setup);
+
+    // `initialize()` doesn't extend the pool's instance, which would be archived by the end of the period
+    setup.pool.checkpoint();
+    advance(&setup.env, ledgers_to_end(&setup) + 1);
+
+    setup.token_admin.mint(&setup.lp, &(1000 * STROOP as i128));
+    setup.pool.deposit(&setup.lp, &(1000 * STROOP as i128));
+
+    setup
+}
+
+// a buyer holding a policy bought with a premium of 10, 200 ledgers into the period
+fn buyer(setup: &Setup) -> Address {
+    let buyer = Address::generate(&setup.env);
+    setup.token_admin.mint(&buyer, &(10 * STROOP as i128));
+
+    advance(&setup.env, 200);
+    setup.pool.subscribe(&buyer, &(10 * STROOP as i128));
+
+    buyer
+}
+
+// ledgers left before the end of the current period
+fn ledgers_to_end(setup: &Setup) -> u32 {
+    let period = setup.pool.read_current_period() as u32;
+    1000 + period * DAY_IN_LEDGERS - setup.env.ledger().sequence()
+}
+
+#[test]
+fn grace_claim_uses_prices_until_the_period_close() {
+    let setup = setup();
+    setup.pool.set_claim_grace(&setup.admin, &1000);
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+
+    // the move happens before the end of the period and the pool is used after it
+    advance(&setup.env, ledgers_to_end(&setup) - 1000);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 400);
+    setup.pool.checkpoint();
+
+    advance(&setup.env, 1000);
+    setup.oracle.set_price(&(20 * STROOP as i128));
+
+    setup.pool.claim_previous(&buyer);
+    assert_eq!(setup.token.balance(&buyer), coverage);
+}
+
+#[test]
+fn grace_claim_uses_prices_after_the_last_interaction() {
+    let setup = setup();
+    setup.pool.set_claim_grace(&setup.admin, &1000);
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+
+    // the move happens in the last minutes of the period, nobody uses the pool until it's over
+    advance(&setup.env, ledgers_to_end(&setup) - 80);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 200);
+    setup.oracle.set_price(&(20 * STROOP as i128));
+
+    setup.pool.claim_previous(&buyer);
+    assert_eq!(setup.token.balance(&buyer), coverage);
+}
+
+#[test]
+fn grace_claim_ignores_prices_after_the_period_close() {
+    let setup = setup();
+    setup.pool.set_claim_grace(&setup.admin, &1000);
+    let buyer = buyer(&setup);
+
+    advance(&setup.env, ledgers_to_end(&setup) - 10);
+    setup.pool.checkpoint();
+
+    // the price only moves once the period is over
+    advance(&setup.env, 20);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+
+    assert_eq!(
+        setup.pool.try_claim_previous(&buyer),
+        Err(Ok(Error::UnmetCondition))
+    );
+}
+
+#[test]
+fn drawdown_claim_reads_history_beyond_the_oracle_lookback() {
+    let setup = setup_with(|setup| {
+        setup
+            .pool
+            .set_trigger_mode(&setup.admin, &TriggerMode::Drawdown);
+    });
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+
+    // the price breaks out of the band 25 timeframes after the purchase, then comes back
+    advance(&setup.env, 1500);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 120);
+    setup.oracle.set_price(&(20 * STROOP as i128));
+
+    // the breakout is neither in the first 20 timeframes of the policy nor in the last 20 records
+    advance(&setup.env, 3000);
+    assert_eq!(
+        setup.pool.try_claim_reward(&buyer),
+        Err(Ok(Error::IncompleteHistory))
+    );
+
+    let period = setup.pool.read_current_period();
+    let now = setup.env.ledger().timestamp();
+    while setup.pool.record_prices(&buyer, &period) < now {}
+
+    setup.pool.claim_reward(&buyer);
+    assert_eq!(setup.token.balance(&buyer), coverage);
+}
+
+#[test]
+fn drawdown_claim_fails_within_the_band() {
+    let setup = setup_with(|setup| {
+        setup
+            .pool
+            .set_trigger_mode(&setup.admin, &TriggerMode::Drawdown);
+    });
+    let buyer = buyer(&setup);
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(21 * STROOP as i128));
+    advance(&setup.env, 3000);
+
+    let period = setup.pool.read_current_period();
+    let now = setup.env.ledger().timestamp();
+    while setup.pool.record_prices(&buyer, &period) < now {}
+
+    assert_eq!(
+        setup.pool.try_claim_reward(&buyer),
+        Err(Ok(Error::UnmetCondition))
+    );
+}
+
+// a linear payout from a 10% move, exhausted at 30%
+fn linear(setup: &Setup) {
+    setup.pool.set_payout_mode(
+        &setup.admin,
+        &PayoutMode::Linear(LinearPayout {
+            attachment: 1_000_000,
+            exhaustion: 3_000_000,
+            coverage_scale: STROOP as i128,
+        }),
+    );
+}
+
+#[test]
+fn keepers_settle_partial_payouts_once_the_period_is_over() {
+    let setup = setup_with(linear);
+    setup.pool.set_claim_grace(&setup.admin, &1000);
+    setup.pool.set_keeper_bounty(&setup.admin, &500_000);
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+    let keeper = Address::generate(&setup.env);
+
+    // a 15% move pays a quarter of the coverage
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(23 * STROOP as i128));
+    advance(&setup.env, 100);
+    assert_eq!(
+        setup.pool.try_settle_claim(&keeper, &buyer),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    setup.pool.checkpoint();
+    advance(&setup.env, ledgers_to_end(&setup) + 10);
+    setup.pool.settle_previous(&keeper, &buyer);
+
+    let paid = linear_payout(coverage, 1_500_000, 1_000_000, 3_000_000);
+    assert_eq!(paid, coverage / 4);
+    assert_eq!(setup.token.balance(&keeper), paid / 20);
+    assert_eq!(setup.token.balance(&buyer), paid - paid / 20);
+}
+
+#[test]
+fn keepers_settle_exhausted_payouts_during_the_period() {
+    let setup = setup_with(linear);
+    setup.pool.set_keeper_bounty(&setup.admin, &500_000);
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+    let keeper = Address::generate(&setup.env);
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(14 * STROOP as i128));
+    advance(&setup.env, 100);
+    setup.pool.settle_claim(&keeper, &buyer);
+
+    assert_eq!(setup.token.balance(&keeper), coverage / 20);
+    assert_eq!(setup.token.balance(&buyer), coverage - coverage / 20);
+}
+
+#[test]
+fn holders_claim_partial_payouts_during_the_period() {
+    let setup = setup_with(linear);
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(23 * STROOP as i128));
+    advance(&setup.env, 100);
+    setup.pool.claim_reward(&buyer);
+
+    assert_eq!(setup.token.balance(&buyer), coverage / 4);
+}
+
+#[test]
+fn payout_mode_changes_wait_for_the_next_period() {
+    let setup = setup();
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+
+    // a linear payout would only pay a quarter of the coverage for this move
+    linear(&setup);
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(23 * STROOP as i128));
+    advance(&setup.env, 100);
+
+    setup.pool.claim_reward(&buyer);
+    assert_eq!(setup.token.balance(&buyer), coverage);
+}
+
+#[test]
+fn oracle_changes_wait_for_the_next_period() {
+    let setup = setup();
+    let buyer = buyer(&setup);
+
+    let oracle_addr = setup
+        .env
+        .register_contract(&None, mock_history::PriceHistoryMock);
+    let oracle = mock_history::PriceHistoryMockClient::new(&setup.env, &oracle_addr);
+    oracle.set_price(&(25 * STROOP as i128));
+
+    setup.pool.set_oracles(
+        &setup.admin,
+        &vec![
+            &setup.env,
+            OracleSource {
+                kind: OracleKind::Reflector,
+                address: oracle_addr.clone(),
+            },
+        ],
+        &OracleQuorum {
+            mode: AggregationMode::Fallback,
+            min_sources: 1,
+            max_divergence: 0,
+        },
+    );
+
+    // the policy is still evaluated against the oracle of its period
+    advance(&setup.env, 100);
+    assert_eq!(
+        setup.pool.try_claim_reward(&buyer),
+        Err(Ok(Error::UnmetCondition))
+    );
+
+    advance(&setup.env, ledgers_to_end(&setup) + 1);
+    let period = setup.pool.read_current_period();
+    let oracles = setup.pool.risk_params(&period).oracles;
+    assert_eq!(oracles.len(), 1);
+    assert_eq!(oracles.get_unchecked(0).address, oracle_addr);
+}
+
+#[test]
+fn policies_sold_before_versioning_can_be_claimed() {
+    let setup = setup();
+    let holder = Address::generate(&setup.env);
+    let period = setup.pool.read_current_period();
+    let amount = 100 * STROOP as i128;
+
+    // a policy as the first deployments stored it
+    setup.env.as_contract(&setup.pool.address, || {
+        setup.env.storage().persistent().set(
+            &PersistentDataKey::RefundParticular(BalanceObject::new(holder.clone(), period)),
+            &LegacyInsurance {
+                amount,
+                price: 20 * STROOP as i128,
+            },
+        );
+        write_refund_global(&setup.env, amount, period);
+    });
+
+    let policy = setup.pool.particular(&holder).3.unwrap();
+    assert_eq!(policy.amount, amount);
+    assert_eq!(policy.token, None);
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+
+    setup.pool.claim_reward(&holder);
+    assert_eq!(setup.token.balance(&holder), amount);
+}
+
+#[test]
+fn sold_policies_are_claimed_with_the_token_owner_auth() {
+    let setup = setup_nft();
+    let buyer = buyer(&setup);
+    let policy = setup.pool.particular(&buyer).3.unwrap();
+
+    let owner = Address::generate(&setup.env);
+    setup.nft.transfer(&policy.token.unwrap(), &owner);
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+
+    setup.pool.claim_reward(&buyer);
+    assert_eq!(setup.env.auths()[0].0, owner);
+    assert_eq!(setup.token.balance(&owner), policy.amount);
+    assert_eq!(setup.token.balance(&buyer), 0);
+}
+
+#[test]
+fn policy_nfts_are_attached_to_the_pool_that_initialized_them() {
+    let setup = setup_nft();
+    assert_eq!(setup.nft.minter(), setup.pool.address);
+
+    // a second pool can't mint from a token contract initialized by the first one
+    let pool = PoolClient::new(&setup.env, &setup.env.register_contract(&None, Pool));
+    let initialized = pool.try_initialize(
+        &setup.admin,
+        &setup.token.address,
+        &setup.oracle.address,
+        &AssetPair {
+            symbol: symbol_short!("XLM"),
+            base: TrackedAsset::External,
+            quote: PairQuote::None,
+        },
+        &1,
+        &VOLATILITY,
+        &3,
+        &Some(setup.nft.address.clone()),
+    );
+    assert_eq!(initialized, Err(Ok(Error::AlreadyInitialized)));
+}
+
+#[test]
+fn shortfalls_of_several_policies_add_up() {
+    let setup = setup_nft();
+    setup.pool.set_pro_rata(&setup.admin, &true);
+    let owner = Address::generate(&setup.env);
+
+    let mut coverage = 0;
+    let mut tokens = std::vec::Vec::new();
+    for _ in 0..2 {
+        let policy = setup.pool.particular(&buyer(&setup)).3.unwrap();
+        setup.nft.transfer(&policy.token.unwrap(), &owner);
+        coverage += policy.amount;
+        tokens.push(policy.token.unwrap());
+    }
+
+    // the pool can only pay half of the coverage it reserved
+    let period = setup.pool.read_current_period();
+    setup.env.as_contract(&setup.pool.address, || {
+        put_tot_liquidity(&setup.env, coverage / 2, period);
+    });
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+
+    for token_id in tokens {
+        setup.pool.claim_policy(&owner, &token_id);
+    }
+
+    let paid = setup.token.balance(&owner);
+    assert!(paid < coverage);
+    assert_eq!(setup.pool.shortfall(&owner, &period), coverage - paid);
+}
+
+#[test]
+fn foreign_premiums_become_fees_once_converted() {
+    let setup = setup();
+    setup.pool.set_referral_commission(&setup.admin, &1_000_000);
+
+    let usdc_id = setup
+        .env
+        .register_stellar_asset_contract(setup.admin.clone());
+    let usdc = token::Client::new(&setup.env, &usdc_id);
+    setup.pool.set_premium_tokens(
+        &setup.admin,
+        &QuoteAsset::Stellar(setup.token.address.clone()),
+        &vec![
+            &setup.env,
+            PremiumToken {
+                token: usdc_id.clone(),
+                asset: QuoteAsset::Other(symbol_short!("USDC")),
+                spread: 0,
+            },
+        ],
+    );
+
+    let buyer = Address::generate(&setup.env);
+    let referrer = Address::generate(&setup.env);
+    setup.pool.set_referrer(&setup.admin, &referrer, &true);
+    token::StellarAssetClient::new(&setup.env, &usdc_id).mint(&buyer, &(10 * STROOP as i128));
+    setup.pool.subscribe_with_token(
+        &buyer,
+        &buyer,
+        &usdc_id,
+        &(10 * STROOP as i128),
+        &Some(referrer.clone()),
+    );
+
+    // the commission is paid in the premium's token, and nothing is credited to liquidity providers yet
+    let period = setup.pool.read_current_period();
+    assert_eq!(usdc.balance(&referrer), STROOP as i128);
+    assert_eq!(setup.pool.referral_earnings(&referrer), 0);
+    assert_eq!(
+        setup.pool.foreign_premiums(&usdc_id, &period),
+        9 * STROOP as i128
+    );
+    setup.pool.update_fee_rewards(&setup.lp, &period);
+    assert_eq!(setup.pool.matured(&setup.lp, &period), 0);
+
+    // the liquidity provider has to wait for the conversion to leave with its share of the premiums
+    advance(&setup.env, ledgers_to_end(&setup) + 1);
+    assert_eq!(
+        setup.pool.try_withdraw(&setup.lp, &period),
+        Err(Ok(Error::CannotWithdraw))
+    );
+
+    let converter = Address::generate(&setup.env);
+    setup.token_admin.mint(&converter, &(9 * STROOP as i128));
+    setup
+        .pool
+        .convert_premiums(&converter, &usdc_id, &period, &(9 * STROOP as i128));
+
+    assert_eq!(usdc.balance(&converter), 9 * STROOP as i128);
+    assert_eq!(setup.pool.foreign_premiums(&usdc_id, &period), 0);
+    setup.pool.withdraw(&setup.lp, &period);
+    assert!(setup.pool.matured(&setup.lp, &period) > 9 * STROOP as i128 - 10);
+}
+
+#[test]
+fn pair_pools_follow_the_cross_price() {
+    let setup = setup_pair();
+    let buyer = buyer(&setup);
+    let policy = setup.pool.particular(&buyer).3.unwrap();
+    assert_eq!(policy.price, 20 * STROOP as i128);
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+
+    setup.pool.claim_reward(&buyer);
+    assert_eq!(setup.token.balance(&buyer), policy.amount);
+}
+
+#[test]
+fn premium_conversions_follow_the_oracle_quorum() {
+    // a second oracle sees the premium token at 20 times the rate of the first one
+    let setup = setup_with(|setup| {
+        let oracle_addr = setup
+            .env
+            .register_contract(&None, mock_history::PriceHistoryMock);
+        let oracle = mock_history::PriceHistoryMockClient::new(&setup.env, &oracle_addr);
+        oracle.set_price(&(20 * STROOP as i128));
+        oracle.set_quote(&Asset::Stellar(setup.token.address.clone()));
+
+        setup.pool.set_oracles(
+            &setup.admin,
+            &vec![
+                &setup.env,
+                OracleSource {
+                    kind: OracleKind::Reflector,
+                    address: setup.oracle.address.clone(),
+                },
+                OracleSource {
+                    kind: OracleKind::Reflector,
+                    address: oracle_addr,
+                },
+            ],
+            &OracleQuorum {
+                mode: AggregationMode::Median,
+                min_sources: 1,
+                max_divergence: 1_000_000,
+            },
+        );
+    });
+
+    let usdc_id = setup
+        .env
+        .register_stellar_asset_contract(setup.admin.clone());
+    setup.pool.set_premium_tokens(
+        &setup.admin,
+        &QuoteAsset::Stellar(setup.token.address.clone()),
+        &vec![
+            &setup.env,
+            PremiumToken {
+                token: usdc_id.clone(),
+                asset: QuoteAsset::Stellar(usdc_id.clone()),
+                spread: 0,
+            },
+        ],
+    );
+
+    let buyer = Address::generate(&setup.env);
+    token::StellarAssetClient::new(&setup.env, &usdc_id).mint(&buyer, &(10 * STROOP as i128));
+    assert_eq!(
+        setup.pool.try_subscribe_with_token(
+            &buyer,
+            &buyer,
+            &usdc_id,
+            &(10 * STROOP as i128),
+            &None
+        ),
+        Err(Ok(Error::OracleDivergence))
+    );
+}
+
+// a pool reading `setup.oracle` and a second oracle reporting `price` with `decimals`, combined under `quorum`
+fn two_oracles<'a>(price: Option<i128>, decimals: u32, quorum: OracleQuorum) -> Setup<'a> {
+    setup_with(|setup| {
+        let oracle_addr = setup
+            .env
+            .register_contract(&None, mock_history::PriceHistoryMock);
+        let oracle = mock_history::PriceHistoryMockClient::new(&setup.env, &oracle_addr);
+        oracle.set_decimals(&decimals);
+        if let Some(price) = price {
+            oracle.set_price(&price);
+        }
+
+        setup.pool.set_oracles(
+            &setup.admin,
+            &vec![
+                &setup.env,
+                OracleSource {
+                    kind: OracleKind::Reflector,
+                    address: oracle_addr,
+                },
+                OracleSource {
+                    kind: OracleKind::Reflector,
+                    address: setup.oracle.address.clone(),
+                },
+            ],
+            &quorum,
+        );
+    })
+}
+
+#[test]
+fn diverging_sources_are_refused() {
+    let setup = two_oracles(
+        Some(25 * STROOP as i128),
+        7,
+        OracleQuorum {
+            mode: AggregationMode::Median,
+            min_sources: 2,
+            max_divergence: 1_000_000,
+        },
+    );
+
+    let buyer = Address::generate(&setup.env);
+    setup.token_admin.mint(&buyer, &(10 * STROOP as i128));
+    assert_eq!(
+        setup.pool.try_subscribe(&buyer, &(10 * STROOP as i128)),
+        Err(Ok(Error::OracleDivergence))
+    );
+
+    // both sources agree again
+    setup.oracle.set_price(&(24 * STROOP as i128));
+    advance(&setup.env, 60);
+    setup.pool.subscribe(&buyer, &(10 * STROOP as i128));
+    assert_eq!(
+        setup.pool.particular(&buyer).3.unwrap().price,
+        245 * STROOP as i128 / 10
+    );
+}
+
+#[test]
+fn fallback_reads_the_next_source_without_a_price() {
+    let setup = two_oracles(
+        None,
+        7,
+        OracleQuorum {
+            mode: AggregationMode::Fallback,
+            min_sources: 1,
+            max_divergence: 0,
+        },
+    );
+
+    let buyer = buyer(&setup);
+    assert_eq!(
+        setup.pool.particular(&buyer).3.unwrap().price,
+        20 * STROOP as i128
+    );
+}
+
+#[test]
+fn sources_with_other_decimals_are_normalized() {
+    // 21 with 8 decimals
+    let setup = two_oracles(
+        Some(2_100_000_000),
+        8,
+        OracleQuorum {
+            mode: AggregationMode::Median,
+            min_sources: 2,
+            max_divergence: 1_000_000,
+        },
+    );
+
+    let buyer = buyer(&setup);
+    assert_eq!(
+        setup.pool.particular(&buyer).3.unwrap().price,
+        205 * STROOP as i128 / 10
+    );
+}
+
+#[test]
+fn rescale_converts_between_token_decimals() {
+    // 1.5 of a 6 decimals token, in a 7 decimals token and back
+    assert_eq!(rescale(1_500_000, 6, 7), 15_000_000);
+    assert_eq!(rescale(15_000_000, 7, 6), 1_500_000);
+
+    // precision beyond the target decimals is dropped
+    assert_eq!(rescale(1_234_567_891, 9, 7), 12_345_678);
+    assert_eq!(rescale(42, 7, 7), 42);
+}
+
+#[test]
+fn only_registered_referrers_earn_commissions() {
+    let setup = setup();
+    setup.pool.set_referral_commission(&setup.admin, &1_000_000);
+
+    let buyer = Address::generate(&setup.env);
+    let referrer = Address::generate(&setup.env);
+    setup.token_admin.mint(&buyer, &(20 * STROOP as i128));
+
+    // a buyer can't earn back part of its premium through an address of its own
+    assert_eq!(
+        setup.pool.try_subscribe_for(
+            &buyer,
+            &buyer,
+            &(10 * STROOP as i128),
+            &Some(referrer.clone())
+        ),
+        Err(Ok(Error::InvalidReferrer))
+    );
+
+    setup.pool.set_referrer(&setup.admin, &referrer, &true);
+    setup.pool.subscribe_for(
+        &buyer,
+        &buyer,
+        &(10 * STROOP as i128),
+        &Some(referrer.clone()),
+    );
+    assert_eq!(setup.pool.referral_earnings(&referrer), STROOP as i128);
+}
+
+// governance voting for 100 ledgers with a 50% quorum and upgrades delayed by 500 ledgers, enabled once `configure`
+// used the owner's roles
+fn pause(setup: &Setup, scope: PauseScope) {
+    setup.pool.pause(&setup.admin, &vec![&setup.env, scope]);
+}
+
+#[test]
+fn paused_deposits_are_refused() {
+    let setup = setup();
+    pause(&setup, PauseScope::Deposits);
+
+    let lp = Address::generate(&setup.env);
+    setup.token_admin.mint(&lp, &(100 * STROOP as i128));
+    assert_eq!(
+        setup.pool.try_deposit(&lp, &(100 * STROOP as i128)),
+        Err(Ok(Error::PausedDeposits))
+    );
+
+    // the other scopes keep running
+    buyer(&setup);
+
+    setup.pool.unpause(&setup.admin, &PauseScope::Deposits);
+    setup.pool.deposit(&lp, &(100 * STROOP as i128));
+}
+
+#[test]
+fn paused_subscriptions_are_refused() {
+    let setup = setup();
+    pause(&setup, PauseScope::Subscriptions);
+
+    let buyer = Address::generate(&setup.env);
+    setup.token_admin.mint(&buyer, &(10 * STROOP as i128));
+    assert_eq!(
+        setup.pool.try_subscribe(&buyer, &(10 * STROOP as i128)),
+        Err(Ok(Error::PausedSubscriptions))
+    );
+
+    let lp = Address::generate(&setup.env);
+    setup.token_admin.mint(&lp, &(100 * STROOP as i128));
+    setup.pool.deposit(&lp, &(100 * STROOP as i128));
+
+    setup.pool.unpause(&setup.admin, &PauseScope::Subscriptions);
+    setup.pool.subscribe(&buyer, &(10 * STROOP as i128));
+}
+
+#[test]
+fn paused_claims_are_refused() {
+    let setup = setup();
+    let buyer = buyer(&setup);
+
+    advance(&setup.env, 100);
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+
+    pause(&setup, PauseScope::Claims);
+    assert_eq!(
+        setup.pool.try_claim_reward(&buyer),
+        Err(Ok(Error::PausedClaims))
+    );
+
+    setup.pool.unpause(&setup.admin, &PauseScope::Claims);
+    setup.pool.claim_reward(&buyer);
+    assert!(setup.token.balance(&buyer) > 0);
+}
+
+#[test]
+fn paused_withdrawals_are_refused() {
+    let setup = setup();
+    let period = setup.pool.read_current_period();
+    buyer(&setup);
+    advance(&setup.env, ledgers_to_end(&setup) + 1);
+
+    pause(&setup, PauseScope::Withdrawals);
+    assert_eq!(
+        setup.pool.try_withdraw(&setup.lp, &period),
+        Err(Ok(Error::PausedWithdrawals))
+    );
+    assert_eq!(
+        setup.pool.try_withdraw_matured(&setup.lp, &period),
+        Err(Ok(Error::PausedWithdrawals))
+    );
+
+    setup.pool.unpause(&setup.admin, &PauseScope::Withdrawals);
+    setup.pool.withdraw(&setup.lp, &period);
+    setup.pool.withdraw_matured(&setup.lp, &period);
+    assert!(setup.token.balance(&setup.lp) > 1000 * STROOP as i128);
+}
+
+fn governed<'a>(configure: impl FnOnce(&Setup)) -> Setup<'a> {
+    let setup = setup();
+    configure(&setup);
+
+    setup.pool.set_governance(
+        &setup.admin,
+        &Some(GovernanceConfig {
+            voting_period: 100,
+            quorum: STROOP as i128 / 2,
+            upgrade_delay: 500,
+        }),
+    );
+
+    // deposits only vote on the proposals created after them
+    advance(&setup.env, 1);
+    setup
+}
+
+// `setup.lp` proposes `action` and votes for it, until the end of the vote
+fn approve(setup: &Setup, action: ProposalAction) -> u32 {
+    let proposal = setup.pool.propose(&setup.lp, &action);
+    setup.pool.vote(&setup.lp, &proposal, &true);
+
+    advance(&setup.env, 101);
+    proposal
+}
+
+#[test]
+fn deposits_made_once_a_proposal_is_open_carry_no_votes() {
+    let setup = governed(|_| {});
+    let proposal = setup.pool.propose(
+        &setup.lp,
+        &ProposalAction::Pause(vec![&setup.env, PauseScope::Deposits]),
+    );
+
+    // a whale joining once the proposal is open can't outvote the liquidity providers
+    let whale = Address::generate(&setup.env);
+    setup.token_admin.mint(&whale, &(10_000 * STROOP as i128));
+    setup.pool.deposit(&whale, &(10_000 * STROOP as i128));
+    assert_eq!(
+        setup.pool.try_vote(&whale, &proposal, &false),
+        Err(Ok(Error::NoVotingPower))
+    );
+
+    // nor can an address that doesn't provide liquidity propose
+    let outsider = Address::generate(&setup.env);
+    assert_eq!(
+        setup.pool.try_propose(
+            &outsider,
+            &ProposalAction::Pause(vec![&setup.env, PauseScope::Claims])
+        ),
+        Err(Ok(Error::NoVotingPower))
+    );
+
+    setup.pool.vote(&setup.lp, &proposal, &true);
+    advance(&setup.env, 101);
+    setup.pool.execute(&proposal);
+    assert!(setup.pool.paused(&PauseScope::Deposits));
+}
+
+#[test]
+fn approved_upgrades_wait_for_the_delay() {
+    let setup = governed(|_| {});
+    let hash = BytesN::from_array(&setup.env, &[7; 32]);
+    let proposal = approve(&setup, ProposalAction::Upgrade(hash));
+    setup.pool.execute(&proposal);
+
+    let unlocks = setup.pool.proposal(&proposal).unwrap().unlocks;
+    assert_eq!(unlocks, setup.env.ledger().sequence() + 500);
+
+    advance(&setup.env, 499);
+    assert_eq!(
+        setup.pool.try_execute(&proposal),
+        Err(Ok(Error::Timelocked))
+    );
+    assert!(!setup.pool.proposal(&proposal).unwrap().executed);
+
+    // the upgrade has a voting period to be completed once unlocked
+    advance(&setup.env, 102);
+    assert_eq!(
+        setup.pool.try_execute(&proposal),
+        Err(Ok(Error::ProposalExpired))
+    );
+}
+
+#[test]
+fn approved_unpauses_complete_after_their_delay() {
+    let setup = governed(|setup| {
+        setup.pool.set_unpause_delay(&setup.admin, &1000);
+        setup
+            .pool
+            .pause(&setup.admin, &vec![&setup.env, PauseScope::Claims]);
+    });
+
+    let proposal = approve(&setup, ProposalAction::Unpause(PauseScope::Claims));
+    setup.pool.execute(&proposal);
+    assert!(setup.pool.paused(&PauseScope::Claims));
+
+    // the unpause delay outlasts the time the vote left to execute the proposal
+    let expires = setup.pool.proposal(&proposal).unwrap().expires;
+    assert!(expires > setup.env.ledger().sequence() + 1000);
+
+    advance(&setup.env, 1000);
+    setup.pool.execute(&proposal);
+    assert!(!setup.pool.paused(&PauseScope::Claims));
+    assert!(setup.pool.proposal(&proposal).unwrap().executed);
+}
+
+#[test]
+fn governed_pools_are_out_of_the_owner_reach() {
+    let setup = governed(|_| {});
+    let pauser = Address::generate(&setup.env);
+    let hash = BytesN::from_array(&setup.env, &[7; 32]);
+
+    assert_eq!(setup.pool.try_update(&hash), Err(Ok(Error::Unauthorized)));
+    assert_eq!(
+        setup.pool.try_set_governance(&setup.admin, &None),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        setup
+            .pool
+            .try_grant_role(&setup.admin, &Role::Pauser, &pauser),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert!(!setup.pool.has_role(&Role::Pauser, &setup.admin));
+    assert_eq!(
+        setup
+            .pool
+            .try_pause(&setup.admin, &vec![&setup.env, PauseScope::Deposits]),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    // roles are handed out by the liquidity providers
+    let proposal = approve(
+        &setup,
+        ProposalAction::GrantRole(Role::Pauser, pauser.clone()),
+    );
+    setup.pool.execute(&proposal);
+    setup
+        .pool
+        .pause(&pauser, &vec![&setup.env, PauseScope::Deposits]);
+
+    // until they hand the pool back to its owner
+    let proposal = approve(&setup, ProposalAction::EndGovernance);
+    setup.pool.execute(&proposal);
+    assert!(setup.pool.has_role(&Role::Pauser, &setup.admin));
+}
+
+// oracle records for `prices`, most recent first
+fn history(env: &Env, prices: &[i128]) -> Vec<reflector::PriceData> {
+    let mut history = Vec::new(env);
+    for price in prices {
+        history.push_back(reflector::PriceData {
+            price: *price,
+            timestamp: 0,
+        });
+    }
+
+    history
+}
+
+#[test]
+fn realized_volatility_averages_absolute_returns() {
+    let env = Env::default();
+
+    // a 10% rise followed by a 10% drop, computed on the older price of each pair
+    let history = history(&env, &[99_000_000, 110_000_000, 100_000_000]);
+    assert_eq!(realized_volatility(&history), Some(1_000_000));
+
+    // a flat history has no volatility
+    let flat = self::history(&env, &[100_000_000, 100_000_000]);
+    assert_eq!(realized_volatility(&flat), Some(0));
+}
+
+#[test]
+fn realized_volatility_needs_two_valid_records() {
+    let env = Env::default();
+
+    assert_eq!(realized_volatility(&history(&env, &[100_000_000])), None);
+    assert_eq!(realized_volatility(&history(&env, &[100_000_000, 0])), None);
+}
+
+#[test]
+fn median_of_odd_and_even_sources() {
+    let env = Env::default();
+
+    assert_eq!(median(&vec![&env, 7]), 7);
+    assert_eq!(median(&vec![&env, 1, 5, 9]), 5);
+
+    // with an even count the two middle prices are averaged, rounding down
+    assert_eq!(median(&vec![&env, 1, 4, 5, 100]), 4);
+}
+
+#[test]
+fn normalize_price_to_seven_decimals() {
+    // 20 reported by oracles with 14, 7 and 2 decimals
+    assert_eq!(normalize_price(2_000_000_000_000_000, 14), 200_000_000);
+    assert_eq!(normalize_price(200_000_000, 7), 200_000_000);
+    assert_eq!(normalize_price(2_000, 2), 200_000_000);
+}
+
+#[test]
+fn linear_payout_between_attachment_and_exhaustion() {
+    let amount = 100 * STROOP as i128;
+
+    // 10% attachment, 30% exhaustion
+    assert_eq!(linear_payout(amount, 1_000_000, 1_000_000, 3_000_000), 0);
+    assert_eq!(
+        linear_payout(amount, 2_000_000, 1_000_000, 3_000_000),
+        amount / 2
+    );
+    assert_eq!(
+        linear_payout(amount, 4_000_000, 1_000_000, 3_000_000),
+        amount
+    );
+}
+
+#[test]
+fn holders_claim_the_coverage_once_the_price_moved() {
+    let setup = setup();
+    let buyer = buyer(&setup);
+    let coverage = setup.pool.particular(&buyer).3.unwrap().amount;
+
+    advance(&setup.env, 100);
+    assert_eq!(setup.pool.claimable_now(&buyer), 0);
+    assert_eq!(
+        setup.pool.try_claim_reward(&buyer),
+        Err(Ok(Error::UnmetCondition))
+    );
+
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+    assert_eq!(setup.pool.claimable_now(&buyer), coverage);
+
+    setup.pool.claim_reward(&buyer);
+    assert_eq!(setup.token.balance(&buyer), coverage);
+    assert_eq!(
+        setup.pool.try_claim_reward(&buyer),
+        Err(Ok(Error::NoInsurance))
+    );
+}
+
+#[test]
+fn batches_sponsor_every_beneficiary_with_one_transfer() {
+    let setup = setup();
+    let sponsor = Address::generate(&setup.env);
+    let first = Address::generate(&setup.env);
+    let second = Address::generate(&setup.env);
+    setup.token_admin.mint(&sponsor, &(30 * STROOP as i128));
+
+    // a beneficiary listed twice fails the whole batch
+    assert_eq!(
+        setup.pool.try_subscribe_batch(
+            &sponsor,
+            &vec![
+                &setup.env,
+                (first.clone(), 10 * STROOP as i128),
+                (first.clone(), 10 * STROOP as i128),
+            ]
+        ),
+        Err(Ok(Error::AlreadySubscribed))
+    );
+    assert!(setup.pool.particular(&first).3.is_none());
+
+    // as does a batch the pool can't cover as a whole, each of its policies fitting alone
+    let whales = vec![
+        &setup.env,
+        (first.clone(), 600 * STROOP as i128),
+        (second.clone(), 600 * STROOP as i128),
+    ];
+    assert_eq!(
+        setup.pool.try_subscribe_batch(&sponsor, &whales),
+        Err(Ok(Error::NotEnoughLiquidity))
+    );
+
+    setup.pool.subscribe_batch(
+        &sponsor,
+        &vec![
+            &setup.env,
+            (first.clone(), 10 * STROOP as i128),
+            (second.clone(), 20 * STROOP as i128),
+        ],
+    );
+    assert_eq!(setup.token.balance(&sponsor), 0);
+
+    let first_coverage = setup.pool.particular(&first).3.unwrap().amount;
+    let second_coverage = setup.pool.particular(&second).3.unwrap().amount;
+    assert!(second_coverage > first_coverage);
+
+    // the beneficiaries, not the sponsor, claim the policies
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 100);
+    setup.pool.claim_reward(&first);
+    setup.pool.claim_reward(&second);
+    assert_eq!(setup.token.balance(&first), first_coverage);
+    assert_eq!(setup.token.balance(&second), second_coverage);
+}
+
+#[test]
+fn proposals_keep_the_decimals_of_the_live_period() {
+    let setup = setup();
+    let price = |setup: &Setup| setup.pool.source_prices().first_unchecked().normalized;
+    assert_eq!(price(&setup), 20 * STROOP as i128);
+
+    // the oracle now reports 8 decimals, a change of guards brings it back to the pool
+    setup.oracle.set_decimals(&8);
+    setup.pool.set_oracle_guards(
+        &setup.admin,
+        &OracleGuards {
+            max_age: 0,
+            max_deviation: 0,
+            min_records: 0,
+        },
+    );
+    assert_eq!(price(&setup), 20 * STROOP as i128);
+
+    advance(&setup.env, ledgers_to_end(&setup) + 1);
+    setup.pool.checkpoint();
+    assert_eq!(price(&setup), 2 * STROOP as i128);
+}
+
+fn guarded<'a>(guards: OracleGuards) -> Setup<'a> {
+    setup_with(|setup| {
+        setup.pool.set_oracle_guards(&setup.admin, &guards);
+    })
+}
+
+#[test]
+fn stale_prices_are_refused() {
+    let setup = guarded(OracleGuards {
+        max_age: 2 * mock_history::RESOLUTION,
+        max_deviation: 0,
+        min_records: 0,
+    });
+    buyer(&setup);
+
+    // the oracle stops publishing for more than two timeframes
+    setup.oracle.stop();
+    advance(&setup.env, 200);
+
+    let buyer = Address::generate(&setup.env);
+    setup.token_admin.mint(&buyer, &(10 * STROOP as i128));
+    assert_eq!(
+        setup.pool.try_subscribe(&buyer, &(10 * STROOP as i128)),
+        Err(Ok(Error::StalePrice))
+    );
+}
+
+#[test]
+fn price_jumps_are_refused_until_confirmed() {
+    let setup = guarded(OracleGuards {
+        max_age: 0,
+        max_deviation: 1_000_000,
+        min_records: 0,
+    });
+    let buyer = Address::generate(&setup.env);
+    setup.token_admin.mint(&buyer, &(20 * STROOP as i128));
+
+    // a 25% move between the last two timeframes is over the 10% allowed
+    setup.oracle.set_price(&(25 * STROOP as i128));
+    advance(&setup.env, 60);
+    assert_eq!(
+        setup.pool.try_subscribe(&buyer, &(10 * STROOP as i128)),
+        Err(Ok(Error::PriceDeviation))
+    );
+
+    // the next timeframe confirms the new price
+    advance(&setup.env, 60);
+    setup.pool.subscribe(&buyer, &(10 * STROOP as i128));
+    assert_eq!(
+        setup.pool.particular(&buyer).3.unwrap().price,
+        25 * STROOP as i128
+    );
+}
+
+#[test]
+fn subscriptions_close_before_the_end_of_the_period() {
+    let setup = setup();
+    setup
+        .pool
+        .set_subscription_cutoff(&setup.admin, &SubscriptionCutoff::Ledgers(1000));
+
+    let buyer = Address::generate(&setup.env);
+    setup.token_admin.mint(&buyer, &(20 * STROOP as i128));
+
+    // the last ledger before the cutoff still sells policies
+    let late = Address::generate(&setup.env);
+    setup.token_admin.mint(&late, &(10 * STROOP as i128));
+    advance(&setup.env, ledgers_to_end(&setup) - 1000);
+    setup.pool.subscribe(&late, &(10 * STROOP as i128));
+
+    advance(&setup.env, 1);
+    assert_eq!(
+        setup.pool.try_subscribe(&buyer, &(10 * STROOP as i128)),
+        Err(Ok(Error::SubscriptionClosed))
+    );
+
+    // and the next period opens them again
+    advance(&setup.env, ledgers_to_end(&setup) + 1);
+    setup.token_admin.mint(&setup.lp, &(1000 * STROOP as i128));
+    setup.pool.deposit(&setup.lp, &(1000 * STROOP as i128));
+    setup.pool.subscribe(&buyer, &(10 * STROOP as i128));
+}
+
+#[test]
+fn policies_of_a_period_share_its_strike() {
+    let setup = setup();
+    setup
+        .pool
+        .set_strike_mode(&setup.admin, &StrikeMode::Period(STROOP as i128 / 2));
+    let first = buyer(&setup);
+    let period = setup.pool.read_current_period();
+    assert_eq!(
+        setup.pool.reference_price(&period),
+        Some(20 * STROOP as i128)
+    );
+
+    // the spot price moved by half the threshold when the second policy is bought
+    setup.oracle.set_price(&(21 * STROOP as i128));
+    advance(&setup.env, 60);
+    let second = Address::generate(&setup.env);
+    setup.token_admin.mint(&second, &(20 * STROOP as i128));
+    setup.pool.subscribe(&second, &(10 * STROOP as i128));
+
+    for buyer in [&first, &second] {
+        assert_eq!(
+            setup.pool.particular(buyer).3.unwrap().price,
+            20 * STROOP as i128
+        );
+    }
+
+    // past that, the period no longer sells policies
+    setup.oracle.set_price(&(22 * STROOP as i128));
+    advance(&setup.env, 60);
+    let third = Address::generate(&setup.env);
+    setup.token_admin.mint(&third, &(10 * STROOP as i128));
+    assert_eq!(
+        setup.pool.try_subscribe(&third, &(10 * STROOP as i128)),
+        Err(Ok(Error::PriceAlreadyMoved))
+    );
+}
+
+#[test]
+fn address_cap_bounds_each_beneficiary() {
+    let setup = setup();
+    let sponsor = Address::generate(&setup.env);
+    setup.token_admin.mint(&sponsor, &(40 * STROOP as i128));
+    advance(&setup.env, 200);
+
+    // a premium of 10 buys a little more than 10 of coverage
+    setup.pool.set_coverage_caps(
+        &setup.admin,
+        &CoverageCaps {
+            max_per_policy: 0,
+            max_per_address: 15 * STROOP as i128,
+            max_pool_share: 0,
+        },
+    );
+
+    // the sponsor pays for more than the cap, but each beneficiary holds less
+    for _ in 0..2 {
+        let beneficiary = Address::generate(&setup.env);
+        setup
+            .pool
+            .subscribe_for(&sponsor, &beneficiary, &(10 * STROOP as i128), &None);
+    }
+
+    let beneficiary = Address::generate(&setup.env);
+    assert_eq!(
+        setup
+            .pool
+            .try_subscribe_for(&sponsor, &beneficiary, &(20 * STROOP as i128), &None),
+        Err(Ok(Error::AddressCapExceeded))
+    );
+}
+
+#[test]
+fn pool_share_cap_bounds_each_beneficiary() {
+    let setup = setup();
+    let sponsor = Address::generate(&setup.env);
+    setup.token_admin.mint(&sponsor, &(40 * STROOP as i128));
+    advance(&setup.env, 200);
+
+    // 1.5% of the 1000 deposited, a premium of 10 buys a little more than 10 of coverage
+    setup.pool.set_coverage_caps(
+        &setup.admin,
+        &CoverageCaps {
+            max_per_policy: 0,
+            max_per_address: 0,
+            max_pool_share: 150_000,
+        },
+    );
+
+    let first = Address::generate(&setup.env);
+    let second = Address::generate(&setup.env);
+    setup.pool.subscribe_batch(
+        &sponsor,
+        &vec![
+            &setup.env,
+            (first, 10 * STROOP as i128),
+            (second, 10 * STROOP as i128),
+        ],
+    );
+
+    let beneficiary = Address::generate(&setup.env);
+    assert_eq!(
+        setup
+            .pool
+            .try_subscribe_for(&sponsor, &beneficiary, &(20 * STROOP as i128), &None),
+        Err(Ok(Error::PoolShareExceeded))
+    );
+}
+
+#[test]
+fn migrations_bring_old_pools_to_the_current_version() {
+    let setup = setup();
+    assert_eq!(setup.pool.version(), (1, 1));
+    assert_eq!(setup.pool.try_migrate(), Err(Ok(Error::UpToDate)));
+
+    // a pool deployed before versions were recorded is refused until it's migrated
+    setup
+        .env
+        .as_contract(&setup.pool.address, || put_version(&setup.env, 0));
+    assert_eq!(setup.pool.try_checkpoint(), Err(Ok(Error::VersionMismatch)));
+
+    assert_eq!(setup.pool.migrate(), 0);
+    assert_eq!(setup.pool.version(), (1, 1));
+    setup.pool.checkpoint();
+
+    // code older than the storage can't migrate it
+    setup
+        .env
+        .as_contract(&setup.pool.address, || put_version(&setup.env, 2));
+    assert_eq!(setup.pool.try_migrate(), Err(Ok(Error::VersionMismatch)));
+    assert_eq!(
+        setup.pool.try_open_period(),
+        Err(Ok(Error::VersionMismatch))
+    );
+}
+
+#[test]
+fn roles_gate_the_settings_until_revoked() {
+    let setup = setup();
+    let manager = Address::generate(&setup.env);
+    let caps = CoverageCaps {
+        max_per_policy: 100 * STROOP as i128,
+        max_per_address: 0,
+        max_pool_share: 0,
+    };
+
+    assert_eq!(
+        setup.pool.try_set_coverage_caps(&manager, &caps),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    setup
+        .pool
+        .grant_role(&setup.admin, &Role::RiskManager, &manager);
+    assert!(setup.pool.has_role(&Role::RiskManager, &manager));
+    setup.pool.set_coverage_caps(&manager, &caps);
+
+    // a role doesn't carry the others, nor the right to hand them out
+    assert_eq!(
+        setup
+            .pool
+            .try_pause(&manager, &vec![&setup.env, PauseScope::Deposits]),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        setup.pool.try_grant_role(&manager, &Role::Pauser, &manager),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    setup
+        .pool
+        .revoke_role(&setup.admin, &Role::RiskManager, &manager);
+    assert_eq!(
+        setup.pool.try_set_coverage_caps(&manager, &caps),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+#[test]
+fn ownership_is_handed_over_in_two_steps() {
+    let setup = setup();
+    let successor = Address::generate(&setup.env);
+
+    assert_eq!(
+        setup.pool.try_propose_admin(&successor, &successor),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        setup.pool.try_accept_admin(),
+        Err(Ok(Error::NoPendingAdmin))
+    );
+
+    setup.pool.propose_admin(&setup.admin, &successor);
+    assert_eq!(setup.pool.pending_admin(), Some(successor.clone()));
+    assert_eq!(setup.pool.admin(), setup.admin);
+
+    setup.pool.accept_admin();
+    assert_eq!(setup.pool.admin(), successor);
+    assert_eq!(setup.pool.pending_admin(), None);
+    assert!(!setup.pool.has_role(&Role::Owner, &setup.admin));
+    assert_eq!(
+        setup
+            .pool
+            .try_grant_role(&setup.admin, &Role::Pauser, &setup.admin),
+        Err(Ok(Error::Unauthorized))
+    );
+}
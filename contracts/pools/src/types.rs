@@ -1,4 +1,6 @@
-use soroban_sdk::{contracterror, contracttype, Address};
+use soroban_sdk::{contracterror, contracttype, Address, BytesN, Symbol, Vec};
+
+use crate::reflector;
 
 #[derive(Clone)]
 #[contracttype]
@@ -12,6 +14,391 @@ pub struct BalanceObject {
 pub struct Insurance {
     pub amount: i128,
     pub price: i128,
+    pub timestamp: u64,
+    pub terms: PolicyTerms,
+    /// id of the policy's token when the pool mints its policies as NFTs.
+    pub token: Option<u64>,
+}
+
+/// The layout policies were stored in before storage versioning. They are read lazily, without a migration.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct LegacyInsurance {
+    pub amount: i128,
+    pub price: i128,
+}
+
+impl From<LegacyInsurance> for Insurance {
+    fn from(legacy: LegacyInsurance) -> Self {
+        Insurance {
+            amount: legacy.amount,
+            price: legacy.price,
+            // only read in `Drawdown` mode, which never applies to the periods legacy policies were sold in
+            timestamp: 0,
+            terms: PolicyTerms::default(),
+            token: None,
+        }
+    }
+}
+
+/// What a policy token stands for, recorded in the policy NFT contract when minting it.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PolicyMetadata {
+    pub pool: Address,
+    pub period: i32,
+    /// coverage of the policy before its deductible and co-insurance.
+    pub coverage: i128,
+    /// price the policy's triggering condition is relative to.
+    pub strike: i128,
+}
+
+/// The policy a token was minted for.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PolicyRef {
+    pub beneficiary: Address,
+    pub period: i32,
+}
+
+/// Loss-sharing terms of a policy. `amount` in `Insurance` is the coverage before they are applied, see
+/// `math::gross_coverage` for how they are priced.
+#[derive(Clone, Debug, Default)]
+#[contracttype]
+pub struct PolicyTerms {
+    /// fixed amount taken from every payout.
+    pub deductible: i128,
+    /// share (7 decimals) of what remains after the deductible that is borne by the policyholder.
+    pub coinsurance: i128,
+}
+
+/// Administrative roles. The owner is the pool's admin and holds every other role.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Role {
+    /// upgrades the pool, grants and revokes the other roles.
+    Owner,
+    /// sets the pricing, oracle, trigger and coverage parameters.
+    RiskManager,
+    /// pauses the pool.
+    Pauser,
+    /// sets the fees and commissions and the accepted premium tokens.
+    Treasury,
+}
+
+/// The operations of a pool that can be paused independently.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PauseScope {
+    Deposits,
+    Subscriptions,
+    /// claims, including shortfall claims.
+    Claims,
+    /// withdrawals of liquidity and matured fees.
+    Withdrawals,
+}
+
+/// The parameters a period's policies are priced and triggered with, frozen for the period at its first use.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct RiskParams {
+    pub volatility: i128,
+    pub multiplier: i32,
+    pub symbol: Symbol,
+    /// the oracle sources, in fallback order.
+    pub oracles: Vec<OracleSource>,
+    pub quorum: OracleQuorum,
+    pub guards: OracleGuards,
+    pub trigger: TriggerMode,
+    pub payout: PayoutMode,
+}
+
+/// How liquidity providers govern the pool. `quorum` is the share of the LP shares voting on a proposal, with 7
+/// decimals, that must vote on it for it to pass.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct GovernanceConfig {
+    pub voting_period: u32,
+    pub quorum: i128,
+    /// ledgers between the approval of an upgrade and the moment it can be carried out.
+    pub upgrade_delay: u32,
+}
+
+/// What an approved proposal does when executed.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum ProposalAction {
+    /// schedules new risk parameters, which still apply from the next period after the minimum delay.
+    RiskParams(RiskParams),
+    PolicyTerms(PolicyTerms),
+    Pause(Vec<PauseScope>),
+    /// unpauses a scope, subject to the pool's unpause delay like any other unpause.
+    Unpause(PauseScope),
+    /// swaps the code of the pool with the uploaded wasm of this hash, once the governance's upgrade delay elapsed.
+    Upgrade(BytesN<32>),
+    /// replaces the governance settings.
+    Governance(GovernanceConfig),
+    /// hands the pool back to its owner.
+    EndGovernance,
+    GrantRole(Role, Address),
+    RevokeRole(Role, Address),
+}
+
+/// A governance proposal. Votes are weighted by the voter's LP shares in `period`, the period the proposal was created
+/// in, as they were before `created`: shares bought once the proposal is open don't vote.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct Proposal {
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub period: i32,
+    pub created: u32,
+    /// the LP shares of `period` at creation, which the quorum is measured on.
+    pub total_weight: i128,
+    /// the last ledger at which votes are accepted.
+    pub voting_ends: u32,
+    /// the last ledger at which the proposal can be executed, pushed back when a timelocked action is scheduled.
+    pub expires: u32,
+    /// for upgrades and delayed unpauses, the ledger from which the execution can be completed, zero until the
+    /// approved proposal was first executed.
+    pub unlocks: u32,
+    pub votes_for: i128,
+    pub votes_against: i128,
+    pub executed: bool,
+}
+
+/// Risk parameters scheduled to replace the current ones from `effective_period`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PendingRiskParams {
+    pub params: RiskParams,
+    pub effective_period: i32,
+    /// the decimals of each of `params.oracles`, recorded once the parameters take effect.
+    pub decimals: Vec<u32>,
+}
+
+/// The asset followed by a pool, as passed to `initialize`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct AssetPair {
+    /// oracle symbol of the followed asset.
+    pub symbol: Symbol,
+    pub base: TrackedAsset,
+    /// for pair pools, the asset the followed asset is priced in.
+    pub quote: PairQuote,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum TrackedAsset {
+    /// the Stellar asset at this address.
+    Stellar(Address),
+    /// an asset the oracle sources from external CEX/DEX, known by its symbol.
+    External,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum PairQuote {
+    /// prices are expressed in the oracle's base asset.
+    None,
+    Stellar(Address),
+    Other(Symbol),
+}
+
+impl PairQuote {
+    pub fn into_reflector(self) -> Option<reflector::Asset> {
+        match self {
+            Self::None => None,
+            Self::Stellar(address) => Some(reflector::Asset::Stellar(address)),
+            Self::Other(symbol) => Some(reflector::Asset::Other(symbol)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum QuoteAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+impl QuoteAsset {
+    pub fn into_reflector(self) -> reflector::Asset {
+        match self {
+            Self::Stellar(address) => reflector::Asset::Stellar(address),
+            Self::Other(symbol) => reflector::Asset::Other(symbol),
+        }
+    }
+}
+
+/// A token premiums can be paid in besides the pool's token, converted at oracle rates.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PremiumToken {
+    pub token: Address,
+    /// the token's asset, as understood by the pool's oracles.
+    pub asset: QuoteAsset,
+    /// share (7 decimals) of the converted premium withheld to cover the cost of selling the token.
+    pub spread: i128,
+}
+
+/// How premiums are converted into coverage at subscription time.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum PricingModel {
+    /// Coverage only depends on `multiplier` and the time left in the period.
+    Fixed,
+    /// `multiplier` is scaled by the realized volatility of the tracked asset.
+    RealizedVolatility(VolatilityPricing),
+}
+
+/// Which oracle prices are checked against a policy's band when claiming.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TriggerMode {
+    /// Only the price at the moment of the claim.
+    Spot,
+    /// Any price recorded since the policy was purchased.
+    Drawdown,
+}
+
+/// The extremes of the prices recorded for a policy in `Drawdown` mode, read from the oracle up to `until`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct ObservedRange {
+    pub low: i128,
+    pub high: i128,
+    pub until: u64,
+}
+
+/// Sanity checks on the oracle feed, a zero value disables the corresponding check.
+#[derive(Clone, Debug, Default)]
+#[contracttype]
+pub struct OracleGuards {
+    /// maximum age in seconds of the last price.
+    pub max_age: u64,
+    /// maximum relative move (7 decimals) between the last two records.
+    pub max_deviation: i128,
+    /// minimum number of records the oracle must return for the tracked asset.
+    pub min_records: u32,
+}
+
+/// The oracle provider interface a source is queried through.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum OracleKind {
+    Reflector,
+    /// see `price_source::SimplePriceFeed`.
+    SimpleFeed,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct OracleSource {
+    pub kind: OracleKind,
+    pub address: Address,
+}
+
+/// Last price of a single oracle source, as reported and normalized to `PRICE_DECIMALS`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PriceInfo {
+    pub source: Address,
+    pub raw: i128,
+    pub decimals: u32,
+    pub normalized: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum AggregationMode {
+    /// The median of the prices of all the sources that passed the `OracleGuards`.
+    Median,
+    /// The price of the first source, in the configured order, that passed the `OracleGuards`.
+    Fallback,
+}
+
+/// How prices from several oracle sources are combined.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct OracleQuorum {
+    pub mode: AggregationMode,
+    /// minimum number of sources with a valid price in `Median` mode.
+    pub min_sources: u32,
+    /// maximum relative distance (7 decimals) of any source from the median, zero disables the check.
+    pub max_divergence: i128,
+}
+
+/// Limits on the coverage a single buyer can hold, measured in the pool's maximum liability. Zero disables a limit.
+#[derive(Clone, Debug, Default)]
+#[contracttype]
+pub struct CoverageCaps {
+    /// maximum coverage of a single policy.
+    pub max_per_policy: i128,
+    /// maximum coverage an address can hold in a period, whoever paid for it.
+    pub max_per_address: i128,
+    /// maximum share (7 decimals) of the period's liquidity an address can hold coverage for.
+    pub max_pool_share: i128,
+}
+
+/// How long before the end of a period subscriptions close.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum SubscriptionCutoff {
+    /// subscriptions stay open until the end of the period.
+    None,
+    /// subscriptions close this many ledgers before the end of the period.
+    Ledgers(u32),
+    /// subscriptions close once the remaining part (7 decimals) of the period is this share of it.
+    Fraction(i128),
+}
+
+/// Which price the triggering condition of a policy is relative to.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum StrikeMode {
+    /// the price at purchase time.
+    Purchase,
+    /// the reference price recorded when the period was opened, shared by all the policies of the period.
+    /// Subscriptions are refused once the price moved from it by more than this share (7 decimals) of the
+    /// triggering threshold.
+    Period(i128),
+}
+
+/// How much a triggered policy pays out.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum PayoutMode {
+    /// The whole coverage once the price moved more than `volatility`.
+    Binary,
+    /// A share of the coverage growing with the size of the price move, like a put spread.
+    Linear(LinearPayout),
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct LinearPayout {
+    /// relative price move (7 decimals) from which the policy starts paying.
+    pub attachment: i128,
+    /// relative price move (7 decimals) from which the policy pays the whole coverage.
+    pub exhaustion: i128,
+    /// factor (7 decimals) applied to the multiplier part of the coverage of new policies.
+    pub coverage_scale: i128,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct VolatilityPricing {
+    /// number of oracle records used to estimate realized volatility (at least 2).
+    pub records: u32,
+    /// realized volatility (mean absolute return per record, 7 decimals) at which `multiplier` applies unchanged.
+    pub target: i128,
+    /// lower bound of the coverage scale (7 decimals).
+    pub min_scale: i128,
+    /// upper bound of the coverage scale (7 decimals).
+    pub max_scale: i128,
 }
 
 impl BalanceObject {
@@ -33,6 +420,36 @@ pub enum InstanceDataKey {
     Volatility,
     Admin,
     Multiplier,
+    PricingModel,
+    KeeperBounty,
+    ClaimGrace,
+    TriggerMode,
+    OracleGuards,
+    Oracles,
+    OracleQuorum,
+    OracleDecimals(Address),
+    QuoteAsset,
+    PayoutMode,
+    PolicyTerms,
+    CoverageCaps,
+    ProRata,
+    ReserveFund,
+    SubscriptionCutoff,
+    StrikeMode,
+    ReferralCommission,
+    PolicyNft,
+    PremiumTokens,
+    TokenAsset,
+    PendingAdmin,
+    Paused(PauseScope),
+    UnpauseDelay,
+    UnpauseAt(PauseScope),
+    PendingRiskParams,
+    RiskDelay,
+    Version,
+    Governance,
+    ProposalCount,
+    LastObserved,
 }
 
 #[derive(Clone)]
@@ -47,6 +464,20 @@ pub enum PersistentDataKey {
     MaturedFeesParticular(BalanceObject),
     RefundParticular(BalanceObject),
     RefundGlobal(i32),
+    AddressCoverage(BalanceObject),
+    Shortfall(BalanceObject),
+    ReferencePrice(i32),
+    ReferralEarnings(Address),
+    Referrer(Address),
+    TokenPolicy(u64),
+    ForeignPremiums(BalanceObject),
+    PeriodRiskParams(i32),
+    Proposal(u32),
+    Vote(u32, Address),
+    PeriodClose(i32),
+    ObservedRange(BalanceObject),
+    RoleMember(Role, Address),
+    DepositHistory(BalanceObject),
 }
 
 #[contracterror]
@@ -66,4 +497,42 @@ pub enum Error {
     NoPrice = 10,
     AlreadySubscribed = 11,
     UnmetCondition = 12,
+    InvalidConfig = 13,
+    ClaimWindowClosed = 14,
+    StalePrice = 15,
+    PriceDeviation = 16,
+    NoQuorum = 17,
+    OracleDivergence = 18,
+    InvalidTerms = 19,
+    PolicyCapExceeded = 20,
+    AddressCapExceeded = 21,
+    PoolShareExceeded = 22,
+    Insolvent = 23,
+    NoShortfall = 24,
+    SubscriptionClosed = 25,
+    PriceAlreadyMoved = 26,
+    InvalidReferrer = 27,
+    NoEarnings = 28,
+    NotTokenOwner = 29,
+    TokenNotAccepted = 30,
+    Unauthorized = 31,
+    NoPendingAdmin = 32,
+    PausedDeposits = 33,
+    PausedSubscriptions = 34,
+    PausedClaims = 35,
+    PausedWithdrawals = 36,
+    /// a scheduled unpause or upgrade can't be carried out yet.
+    Timelocked = 37,
+    VersionMismatch = 38,
+    UpToDate = 39,
+    GovernanceDisabled = 40,
+    NoProposal = 41,
+    VotingClosed = 42,
+    VotingOpen = 43,
+    AlreadyVoted = 44,
+    NoVotingPower = 45,
+    ProposalRejected = 46,
+    AlreadyExecuted = 47,
+    ProposalExpired = 48,
+    IncompleteHistory = 49,
 }